@@ -3,30 +3,70 @@ use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::render::WindowCanvas;
 use sdl2::rect::Rect;
-use sdl2::Sdl;
+use sdl2::controller::GameController;
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
+use sdl2::{Sdl, GameControllerSubsystem};
 
-use std::time::{Duration, Instant};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::thread;
 
 use ::hardware::system::System;
+use ::hardware::boot_rom::BootRom;
+use ::hardware::ppu::Renderer;
+use ::hardware::joypad::Button;
+use super::input::{InputBindings, HostInput, ControllerInput, HeldButtons};
 
-const CLOCK_SPEED      : i32 = 4194304; // 4.194304 MHz
 const FRAME_RATE       : i32 = 60; // 59.727500569606 Hz
-const CYCLES_PER_FRAME : u32 = (CLOCK_SPEED / FRAME_RATE) as u32;
+const SAVE_INTERVAL    : u32 = 5 * FRAME_RATE as u32; // Flush the save RAM every 5 seconds
+const SAMPLE_RATE      : i32 = 48_000;
+const AUDIO_BUFFER     : u16 = 1024; // Samples per channel handed to the device at once
+pub const AUDIO_LATENCY: u32 = 3; // Frames of audio kept queued when the audio drives the pace
+const AUDIO_MAX_LATENCY: u32 = 10; // Frames of audio queued before samples are dropped
+
+/// How the emulator outputs sound and keeps its pace
+#[derive(Clone, Copy, PartialEq)]
+pub enum AudioMode {
+    /// No sound; frames are timed against the expected frame time
+    Disabled,
+    /// Sound is queued, while frames are timed against the expected frame time
+    Enabled,
+    /// Sound is queued, and its fill level drives the emulation speed
+    Synced,
+}
 
 pub struct EmulatorContext {
     context: Sdl,
     canvas: WindowCanvas,
     hardware: System,
+    file_path: String,
+    bindings: InputBindings,
+    held_buttons: HeldButtons,
+    // Without it (e.g. it failed to initialise) the emulator runs without controllers
+    controller_subsystem: Option<GameControllerSubsystem>,
+    // Opened game controllers by their joystick instance id
+    controllers: HashMap<u32, GameController>,
+    audio_mode: AudioMode,
+    audio_queue: Option<AudioQueue<f32>>,
 }
 
 impl EmulatorContext {
 
-    pub fn new(file_path: String) -> EmulatorContext {
+    pub fn new(file_path: String, boot_rom: Option<BootRom>, bindings: InputBindings, audio_mode: AudioMode) -> EmulatorContext {
         let title = "GameBoy Emulator".to_owned();
-        let system = System::new(file_path.to_owned());
+        let mut system = System::new(file_path.to_owned(), boot_rom);
         let context = sdl2::init().unwrap();
         let video_subsystem = context.video().unwrap();
+        let controller_subsystem = match context.game_controller() {
+            Ok(controller_subsystem) => Some(controller_subsystem),
+            Err(error) => {
+                eprintln!("Could not initialise the game controllers ({}); running without them", error);
+                None
+            },
+        };
         let window = video_subsystem.window(&title, 160, 144)
             .position_centered()
             .build()
@@ -40,21 +80,56 @@ impl EmulatorContext {
         canvas.clear();
         canvas.present();
 
+        let audio_queue: Option<AudioQueue<f32>> = match audio_mode {
+            AudioMode::Disabled => None,
+            _ => match open_audio_queue(&context) {
+                Ok(audio_queue) => Some(audio_queue),
+                Err(error) => {
+                    eprintln!("Could not open the audio device ({}); running without sound", error);
+                    None
+                },
+            },
+        };
+        let audio_mode = match audio_queue {
+            Some(ref audio_queue) => {
+                system.set_audio_sample_rate(audio_queue.spec().freq as u32);
+                audio_queue.resume();
+                audio_mode
+            },
+            None => AudioMode::Disabled,
+        };
+
         EmulatorContext {
             context: context,
             canvas: canvas,
             hardware: system,
+            file_path: file_path,
+            bindings: bindings,
+            held_buttons: HeldButtons::new(),
+            controller_subsystem: controller_subsystem,
+            controllers: HashMap::new(),
+            audio_mode: audio_mode,
+            audio_queue: audio_queue,
         }
     }
 
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.hardware.set_renderer(renderer);
+    }
+
+    pub fn set_access_restrictions(&mut self, enabled: bool) {
+        self.hardware.set_access_restrictions(enabled);
+    }
+
     pub fn run(&mut self) {
         let mut event_pump = self.context.event_pump().unwrap();
         let frame_time = Duration::from_nanos(16_750_418);
 
+        let mut frames_since_save: u32 = 0;
+
         self.hardware.boot();
         'running: loop {
             let start_time = Instant::now();
-            let mut emulated_cycles: u32 = 0;
             let elapsed_time;
 
             for event in event_pump.poll_iter() {
@@ -63,28 +138,141 @@ impl EmulatorContext {
                     Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                         break 'running
                     },
+                    Event::KeyDown { keycode: Some(Keycode::F9), repeat: false, .. } => {
+                        self.toggle_audio_recording();
+                    },
+                    Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+                        if let Some(button) = self.bindings.keyboard_button(keycode) {
+                            self.press(HostInput::Key(keycode), button);
+                        }
+                    },
+                    Event::KeyUp { keycode: Some(keycode), .. } => {
+                        self.release(HostInput::Key(keycode));
+                    },
+                    // SDL also reports the controllers connected at startup as added devices
+                    Event::ControllerDeviceAdded { which, .. } => {
+                        if let Some(ref controller_subsystem) = self.controller_subsystem {
+                            if let Ok(controller) = controller_subsystem.open(which) {
+                                self.controllers.insert(controller.instance_id() as u32, controller);
+                            }
+                        }
+                    },
+                    Event::ControllerDeviceRemoved { which, .. } => {
+                        self.controllers.remove(&which);
+                        for button in self.held_buttons.release_controller(which) {
+                            self.hardware.release(button);
+                        }
+                    },
+                    Event::ControllerButtonDown { which, button, .. } => {
+                        if let Some(bound_button) = self.bindings.controller_button(button) {
+                            self.press(HostInput::Controller(which, ControllerInput::Button(button)), bound_button);
+                        }
+                    },
+                    Event::ControllerButtonUp { which, button, .. } => {
+                        self.release(HostInput::Controller(which, ControllerInput::Button(button)));
+                    },
+                    Event::ControllerAxisMotion { which, axis, value, .. } => {
+                        for (input, button, held) in self.bindings.controller_axis(axis, value) {
+                            match held {
+                                true  => self.press(HostInput::Controller(which, input), button),
+                                false => self.release(HostInput::Controller(which, input)),
+                            }
+                        }
+                    },
                     _ => {}
                 }
             }
 
-            while emulated_cycles < CYCLES_PER_FRAME {
-                emulated_cycles += self.hardware.cycle() as u32;
-                if self.hardware.has_stopped() {
-                    // break 'running
-                    // Keep windown open
-                }
-            }
+            self.hardware.run_frame();
+
+            self.queue_audio();
 
-            elapsed_time = start_time.elapsed();
-            if elapsed_time < frame_time {
-                let remaining_time = frame_time - elapsed_time;
-                thread::sleep(remaining_time);
+            match self.audio_mode {
+                AudioMode::Synced => self.wait_audio(),
+                _ => {
+                    elapsed_time = start_time.elapsed();
+                    if elapsed_time < frame_time {
+                        let remaining_time = frame_time - elapsed_time;
+                        thread::sleep(remaining_time);
+                    }
+                },
             }
 
-            // Update the whole canvas on VBLANK only
-            if self.hardware.video_mode() == ::hardware::video_mode::VBLANK {
+            // Update the whole canvas only when there's a new picture (e.g. not while the LCD is off)
+            if self.hardware.frame_updated() {
                 self.update_canvas();
             }
+
+            frames_since_save += 1;
+            if frames_since_save >= SAVE_INTERVAL && self.hardware.save_ram_dirty() {
+                frames_since_save = 0;
+                self.flush_save_ram();
+            }
+        }
+
+        if self.hardware.is_recording_audio() {
+            self.toggle_audio_recording();
+        }
+        self.flush_save_ram();
+    }
+
+    fn press(&mut self, input: HostInput, button: Button) {
+        if self.held_buttons.press(input, button) {
+            self.hardware.press(button);
+        }
+    }
+
+    fn release(&mut self, input: HostInput) {
+        if let Some(button) = self.held_buttons.release(input) {
+            self.hardware.release(button);
+        }
+    }
+
+    /// Starts recording the audio to `<rom>-<timestamp>.wav` (next to the ROM), or stops it
+    fn toggle_audio_recording(&mut self) {
+        if self.hardware.is_recording_audio() {
+            match self.hardware.stop_audio_recording() {
+                Ok(()) => println!("Audio recording stopped"),
+                Err(error) => eprintln!("Could not write the audio recording: {}", error),
+            }
+            return;
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        let recording_path = format!("{}-{}.wav", Path::new(&self.file_path).with_extension("").display(), timestamp);
+        let result = File::create(&recording_path)
+            .and_then(|file| self.hardware.start_audio_recording(BufWriter::new(file)));
+        match result {
+            Ok(()) => println!("Recording audio to {}", recording_path),
+            Err(error) => eprintln!("Could not record audio to {}: {}", recording_path, error),
+        }
+    }
+
+    /// Hands the samples of the last frame to the audio device; without the audio driving
+    /// the pace, samples are dropped when too many of them are waiting
+    fn queue_audio(&mut self) {
+        let samples: Vec<f32> = self.hardware.audio_samples();
+        if let Some(ref audio_queue) = self.audio_queue {
+            let frame_size: u32 = audio_frame_size(audio_queue);
+            if self.audio_mode == AudioMode::Synced || audio_queue.size() < frame_size * AUDIO_MAX_LATENCY {
+                audio_queue.queue(&samples);
+            }
+        }
+    }
+
+    /// Waits until the audio device has consumed the samples beyond the expected latency
+    fn wait_audio(&self) {
+        if let Some(ref audio_queue) = self.audio_queue {
+            let frame_size: u32 = audio_frame_size(audio_queue);
+            while audio_queue.size() > frame_size * AUDIO_LATENCY {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+
+    fn flush_save_ram(&mut self) {
+        if let Err(error) = self.hardware.flush_save_ram() {
+            eprintln!("Could not write save file: {}", error);
         }
     }
 
@@ -114,3 +302,19 @@ impl EmulatorContext {
     }
 
 }
+
+pub fn open_audio_queue(context: &Sdl) -> Result<AudioQueue<f32>, String> {
+    let audio_subsystem = context.audio()?;
+    let desired_spec = AudioSpecDesired {
+        freq: Some(SAMPLE_RATE),
+        channels: Some(2),
+        samples: Some(AUDIO_BUFFER),
+    };
+    audio_subsystem.open_queue::<f32, _>(None, &desired_spec)
+}
+
+/// Size (in bytes) of a frame of queued audio
+pub fn audio_frame_size(audio_queue: &AudioQueue<f32>) -> u32 {
+    let spec = audio_queue.spec();
+    (spec.freq as u32 / FRAME_RATE as u32) * spec.channels as u32 * std::mem::size_of::<f32>() as u32
+}
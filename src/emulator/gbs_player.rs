@@ -0,0 +1,97 @@
+use sdl2::pixels::Color;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use std::time::{Duration, Instant};
+use std::thread;
+
+use ::hardware::gbs::GbsPlayer;
+use super::context::{self, AUDIO_LATENCY};
+
+/// Plays a GBS file (the `gbc <file.gbs>` mode); Left/Right select the previous/next song,
+/// Escape quits. The window title shows the song being played.
+pub fn run(file_path: String, song: Option<u8>) {
+    let mut player = GbsPlayer::from_file(&file_path).expect("Could not load GBS file; aborting");
+    let context = sdl2::init().unwrap();
+    let video_subsystem = context.video().unwrap();
+    let window = video_subsystem.window(&title(&player), 320, 40)
+        .position_centered()
+        .build()
+        .unwrap();
+    let mut canvas = window.into_canvas().build().unwrap();
+    let mut event_pump = context.event_pump().unwrap();
+    let frame_time = Duration::from_nanos(16_750_418);
+
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    canvas.clear();
+    canvas.present();
+
+    let audio_queue = match context::open_audio_queue(&context) {
+        Ok(audio_queue) => Some(audio_queue),
+        Err(error) => {
+            eprintln!("Could not open the audio device ({}); running without sound", error);
+            None
+        },
+    };
+    if let Some(ref audio_queue) = audio_queue {
+        player.set_audio_sample_rate(audio_queue.spec().freq as u32);
+        audio_queue.resume();
+    }
+    if let Some(song) = song {
+        player.start(song);
+    }
+
+    'running: loop {
+        let start_time = Instant::now();
+        let mut song_changed = false;
+
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit {..} |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                    break 'running
+                },
+                Event::KeyDown { keycode: Some(Keycode::Right), .. } => {
+                    player.next();
+                    song_changed = true;
+                },
+                Event::KeyDown { keycode: Some(Keycode::Left), .. } => {
+                    player.previous();
+                    song_changed = true;
+                },
+                _ => {}
+            }
+        }
+
+        if song_changed {
+            canvas.window_mut().set_title(&title(&player)).unwrap();
+            if let Some(ref audio_queue) = audio_queue {
+                audio_queue.clear();
+            }
+        }
+
+        player.run_frame();
+        let samples: Vec<f32> = player.audio_samples();
+
+        match audio_queue {
+            Some(ref audio_queue) => {
+                audio_queue.queue(&samples);
+                let frame_size: u32 = context::audio_frame_size(audio_queue);
+                while audio_queue.size() > frame_size * AUDIO_LATENCY {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            },
+            None => {
+                let elapsed_time = start_time.elapsed();
+                if elapsed_time < frame_time {
+                    thread::sleep(frame_time - elapsed_time);
+                }
+            },
+        }
+    }
+}
+
+fn title(player: &GbsPlayer) -> String {
+    let header = player.header();
+    format!("{} - {} [{}/{}]", header.title, header.author, player.song() as u16 + 1, header.songs)
+}
@@ -0,0 +1,65 @@
+use std::fs::File;
+use std::io::prelude::*;
+use std::process;
+
+use ::hardware::cartridge;
+use ::hardware::cartridge_header::{CartridgeHeader, ColorSupport, Destination};
+
+/// Prints the decoded cartridge header of a ROM file (the `gbc info <rom>` mode)
+pub fn print(file_path: String) {
+    let mut rom_file = File::open(&file_path).expect("Could not find ROM file; aborting");
+    let mut rom_buffer: Vec<u8> = Vec::new();
+    rom_file.read_to_end(&mut rom_buffer).expect("Could not load ROM file; aborting");
+
+    let header = match CartridgeHeader::parse(&rom_buffer) {
+        Ok(header) => header,
+        Err(error) => {
+            eprintln!("{}: {}", file_path, error);
+            process::exit(1);
+        },
+    };
+
+    print!("{}", describe(&file_path, &rom_buffer, &header));
+}
+
+/// Formats a parsed cartridge header as the report printed by `gbc info`
+pub fn describe(file_path: &str, rom: &[u8], header: &CartridgeHeader) -> String {
+    let color_support = match header.color_support {
+        ColorSupport::None => "No",
+        ColorSupport::Supported => "Supported",
+        ColorSupport::Only => "CGB only",
+    };
+    let destination = match header.destination {
+        Destination::Japan => "Japan".to_owned(),
+        Destination::Overseas => "Overseas".to_owned(),
+        Destination::Unknown(code) => format!("Unknown ({:#04X})", code),
+    };
+    let licensee = match header.old_licensee_code {
+        0x33 => format!("{} (new code \"{}\")", header.licensee(), header.new_licensee_code),
+        code => format!("{} (old code {:#04X})", header.licensee(), code),
+    };
+    let check = |matches: bool| if matches { "OK" } else { "MISMATCH" };
+    let mut report = String::new();
+
+    report.push_str(&format!("File            : {} ({} bytes)\n", file_path, rom.len()));
+    report.push_str(&format!("Title           : {}\n", header.title));
+    report.push_str(&format!("Licensee        : {}\n", licensee));
+    report.push_str(&format!("Destination     : {}\n", destination));
+    report.push_str(&format!("Version         : {:#04X}\n", header.version));
+    report.push_str(&format!("CGB             : {}\n", color_support));
+    report.push_str(&format!("SGB             : {}\n", if header.sgb_support { "Supported" } else { "No" }));
+    report.push_str(&format!("Cartridge Type  : {:#04X} {}\n", header.cartridge_type, header.cartridge_type_name()));
+    report.push_str(&format!("Supported       : {}\n", if cartridge::is_supported(header.cartridge_type) { "Yes" } else { "No" }));
+    report.push_str(&format!("ROM Size        : {:#04X} {} kB ({} banks)\n", header.rom_size_code, header.rom_size() / 1024, header.rom_banks()));
+    report.push_str(&format!("RAM Size        : {:#04X} {} kB\n", header.ram_size_code, header.ram_size() / 1024));
+    if header.rom_size() != rom.len() {
+        report.push_str("                  file size does not match the ROM size at the header\n");
+    }
+    report.push_str(&format!("Nintendo Logo   : {}\n", check(header.logo_matches)));
+    report.push_str(&format!("Header Checksum : stored {:#04X}, computed {:#04X} {}\n",
+        header.header_checksum, header.computed_header_checksum, check(header.header_checksum_matches())));
+    report.push_str(&format!("Global Checksum : stored {:#06X}, computed {:#06X} {}\n",
+        header.global_checksum, header.computed_global_checksum, check(header.global_checksum_matches())));
+
+    report
+}
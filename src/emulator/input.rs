@@ -0,0 +1,276 @@
+use sdl2::keyboard::Keycode;
+use sdl2::controller::{Axis, Button as ControllerButton};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+use ::hardware::joypad::Button;
+
+const DEFAULT_DEADZONE : u16 = 16_000;
+const MAX_DEADZONE     : u16 = 32_767; // An axis reaches -32768 to 32767
+
+const BUTTONS: [(&str, Button); 8] = [
+    ("right",  Button::Right),
+    ("left",   Button::Left),
+    ("up",     Button::Up),
+    ("down",   Button::Down),
+    ("a",      Button::A),
+    ("b",      Button::B),
+    ("select", Button::Select),
+    ("start",  Button::Start),
+];
+
+/// A game controller input: either a button or one direction of an axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControllerInput {
+    Button(ControllerButton),
+    /// The boolean is true for the positive direction of the axis
+    Axis(Axis, bool),
+}
+
+/// A host input that may hold a GAME BOY button down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HostInput {
+    Key(Keycode),
+    /// A controller input, along with the joystick instance id of its controller
+    Controller(u32, ControllerInput),
+}
+
+/// Bindings between the host inputs (SDL keyboard keys and game controller inputs) and
+/// the GAME BOY buttons. Bindings are loaded from a file with one binding per line:
+///
+/// ```text
+/// # Keyboard keys use the SDL key names
+/// keyboard.a = X
+/// keyboard.start = Return, Space
+/// # Controller inputs use the SDL game controller names; axes take a direction (+/-)
+/// controller.up = dpup, lefty-
+/// controller.deadzone = 16000
+/// ```
+///
+/// Buttons not mentioned in the file keep their default bindings.
+pub struct InputBindings {
+    keyboard: HashMap<Keycode, Button>,
+    controller: HashMap<ControllerInput, Button>,
+    deadzone: u16,
+}
+
+impl Default for InputBindings {
+
+    fn default() -> InputBindings {
+        InputBindings::new()
+    }
+
+}
+
+impl InputBindings {
+
+    pub fn new() -> InputBindings {
+        let keyboard: Vec<(Keycode, Button)> = vec![
+            (Keycode::Right,     Button::Right),
+            (Keycode::Left,      Button::Left),
+            (Keycode::Up,        Button::Up),
+            (Keycode::Down,      Button::Down),
+            (Keycode::X,         Button::A),
+            (Keycode::Z,         Button::B),
+            (Keycode::Backspace, Button::Select),
+            (Keycode::Return,    Button::Start),
+        ];
+        // The GAME BOY A button sits at the right of B, like the B and A buttons
+        // of most SDL-recognised game controllers
+        let controller: Vec<(ControllerInput, Button)> = vec![
+            (ControllerInput::Button(ControllerButton::DPadRight), Button::Right),
+            (ControllerInput::Button(ControllerButton::DPadLeft),  Button::Left),
+            (ControllerInput::Button(ControllerButton::DPadUp),    Button::Up),
+            (ControllerInput::Button(ControllerButton::DPadDown),  Button::Down),
+            (ControllerInput::Axis(Axis::LeftX, true),             Button::Right),
+            (ControllerInput::Axis(Axis::LeftX, false),            Button::Left),
+            (ControllerInput::Axis(Axis::LeftY, false),            Button::Up),
+            (ControllerInput::Axis(Axis::LeftY, true),             Button::Down),
+            (ControllerInput::Button(ControllerButton::B),         Button::A),
+            (ControllerInput::Button(ControllerButton::A),         Button::B),
+            (ControllerInput::Button(ControllerButton::Back),      Button::Select),
+            (ControllerInput::Button(ControllerButton::Start),     Button::Start),
+        ];
+
+        InputBindings {
+            keyboard: keyboard.into_iter().collect(),
+            controller: controller.into_iter().collect(),
+            deadzone: DEFAULT_DEADZONE,
+        }
+    }
+
+    pub fn from_file(file_path: &str) -> Result<InputBindings, String> {
+        let mut file = File::open(file_path).map_err(|error| format!("{}: {}", file_path, error))?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).map_err(|error| format!("{}: {}", file_path, error))?;
+        InputBindings::parse(&contents).map_err(|error| format!("{}: {}", file_path, error))
+    }
+
+    pub fn parse(contents: &str) -> Result<InputBindings, String> {
+        let mut bindings = InputBindings::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim().to_lowercase();
+            let value = match parts.next() {
+                Some(value) => value.trim(),
+                None => return Err(format!("line {}: expected `<device>.<button> = <inputs>`", index + 1)),
+            };
+
+            if name == "controller.deadzone" {
+                bindings.deadzone = match value.parse::<u16>() {
+                    Ok(deadzone) if deadzone <= MAX_DEADZONE => deadzone,
+                    _ => return Err(format!("line {}: invalid deadzone `{}` (0 to {})", index + 1, value, MAX_DEADZONE)),
+                };
+                continue;
+            }
+
+            let mut name_parts = name.splitn(2, '.');
+            let device = name_parts.next().unwrap_or("");
+            let button = match name_parts.next().and_then(button_from_name) {
+                Some(button) => button,
+                None => return Err(format!("line {}: unknown GAME BOY button `{}`", index + 1, name)),
+            };
+            let inputs = value.split(',').map(|input| input.trim()).filter(|input| !input.is_empty());
+
+            match device {
+                "keyboard" => {
+                    bindings.keyboard.retain(|_, bound| *bound != button);
+                    for input in inputs {
+                        match Keycode::from_name(input) {
+                            Some(keycode) => { bindings.keyboard.insert(keycode, button); },
+                            None => return Err(format!("line {}: unknown key `{}`", index + 1, input)),
+                        }
+                    }
+                },
+                "controller" => {
+                    bindings.controller.retain(|_, bound| *bound != button);
+                    for input in inputs {
+                        match controller_input_from_name(input) {
+                            Some(controller_input) => { bindings.controller.insert(controller_input, button); },
+                            None => return Err(format!("line {}: unknown controller input `{}`", index + 1, input)),
+                        }
+                    }
+                },
+                _ => return Err(format!("line {}: unknown device `{}` (keyboard or controller)", index + 1, device)),
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    pub fn keyboard_button(&self, keycode: Keycode) -> Option<Button> {
+        self.keyboard.get(&keycode).cloned()
+    }
+
+    pub fn controller_button(&self, button: ControllerButton) -> Option<Button> {
+        self.controller.get(&ControllerInput::Button(button)).cloned()
+    }
+
+    pub fn deadzone(&self) -> u16 {
+        self.deadzone
+    }
+
+    /// Returns the bound directions of the axis, along with their GAME BOY button and
+    /// whether the axis motion holds them (beyond the deadzone)
+    pub fn controller_axis(&self, axis: Axis, value: i16) -> Vec<(ControllerInput, Button, bool)> {
+        let mut directions: Vec<(ControllerInput, Button, bool)> = Vec::new();
+
+        for &positive in [true, false].iter() {
+            let input = ControllerInput::Axis(axis, positive);
+            if let Some(button) = self.controller.get(&input).cloned() {
+                let held = match positive {
+                    true  => value as i32 > self.deadzone as i32,
+                    false => (value as i32) < -(self.deadzone as i32),
+                };
+                directions.push((input, button, held));
+            }
+        }
+
+        directions
+    }
+
+}
+
+/// The host inputs holding each GAME BOY button down; a button is released once none of
+/// its inputs holds it anymore (e.g. a key and a controller button bound to it)
+pub struct HeldButtons {
+    inputs: HashMap<HostInput, Button>,
+}
+
+impl Default for HeldButtons {
+
+    fn default() -> HeldButtons {
+        HeldButtons::new()
+    }
+
+}
+
+impl HeldButtons {
+
+    pub fn new() -> HeldButtons {
+        HeldButtons {
+            inputs: HashMap::new(),
+        }
+    }
+
+    /// Returns whether the button has just been pressed (no other input was holding it)
+    pub fn press(&mut self, input: HostInput, button: Button) -> bool {
+        if self.inputs.contains_key(&input) {
+            return false;
+        }
+        let pressed = !self.is_held(button);
+        self.inputs.insert(input, button);
+        pressed
+    }
+
+    /// Returns the button that has just been released (no other input is holding it)
+    pub fn release(&mut self, input: HostInput) -> Option<Button> {
+        match self.inputs.remove(&input) {
+            Some(button) if !self.is_held(button) => Some(button),
+            _ => None,
+        }
+    }
+
+    /// Releases every input of a controller (e.g. when it's unplugged); returns the
+    /// buttons that are no longer held
+    pub fn release_controller(&mut self, controller_id: u32) -> Vec<Button> {
+        let inputs: Vec<HostInput> = self.inputs.keys()
+            .filter(|input| match **input {
+                HostInput::Controller(id, _) => id == controller_id,
+                HostInput::Key(_) => false,
+            })
+            .cloned()
+            .collect();
+        inputs.into_iter().filter_map(|input| self.release(input)).collect()
+    }
+
+    pub fn is_held(&self, button: Button) -> bool {
+        self.inputs.values().any(|&held| held == button)
+    }
+
+}
+
+fn button_from_name(name: &str) -> Option<Button> {
+    BUTTONS.iter()
+        .find(|&&(button_name, _)| button_name == name)
+        .map(|&(_, button)| button)
+}
+
+/// Controller buttons use the SDL names (e.g. `a`, `dpup`, `start`); axes use the SDL
+/// names followed by the direction (e.g. `leftx-`, `lefty+`), in any case
+fn controller_input_from_name(name: &str) -> Option<ControllerInput> {
+    let name: String = name.to_lowercase();
+    if name.ends_with('+') || name.ends_with('-') {
+        let (axis_name, direction) = name.split_at(name.len() - 1);
+        return Axis::from_string(axis_name).map(|axis| ControllerInput::Axis(axis, direction == "+"));
+    }
+    ControllerButton::from_string(&name).map(ControllerInput::Button)
+}
@@ -1 +1,5 @@
 pub mod context;
+pub mod gbs_player;
+pub mod info;
+pub mod input;
+pub mod record;
@@ -0,0 +1,45 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use ::hardware::system::System;
+use ::hardware::boot_rom::BootRom;
+use ::hardware::gbs::GbsPlayer;
+use ::hardware::wav::WavWriter;
+use ::hardware::apu;
+
+/// Renders the audio of the first frames of a ROM to a WAV file, without opening a window
+/// (the `gbc record <rom> <output.wav>` mode)
+pub fn run_rom(file_path: String, boot_rom: Option<BootRom>, output_path: String, frames: usize) {
+    let mut system = System::new(file_path, boot_rom);
+    let output = File::create(&output_path).expect("Could not create the WAV file; aborting");
+
+    system.start_audio_recording(BufWriter::new(output)).expect("Could not write the WAV file; aborting");
+    system.boot();
+    for _ in 0..frames {
+        system.run_frame();
+        system.audio_samples();
+    }
+    system.stop_audio_recording().expect("Could not write the WAV file; aborting");
+
+    println!("{} frames of audio recorded to {}", frames, output_path);
+}
+
+/// Renders the first frames of a GBS song (0-based) to a WAV file, without opening a window
+pub fn run_gbs(file_path: String, song: Option<u8>, output_path: String, frames: usize) {
+    let mut player = GbsPlayer::from_file(&file_path).expect("Could not load GBS file; aborting");
+    let output = File::create(&output_path).expect("Could not create the WAV file; aborting");
+    let mut recording = WavWriter::new(BufWriter::new(output), apu::DEFAULT_SAMPLE_RATE)
+        .expect("Could not write the WAV file; aborting");
+
+    player.set_audio_sample_rate(apu::DEFAULT_SAMPLE_RATE);
+    if let Some(song) = song {
+        player.start(song);
+    }
+    for _ in 0..frames {
+        player.run_frame();
+        recording.write_samples(&player.audio_samples()).expect("Could not write the WAV file; aborting");
+    }
+    recording.finish().expect("Could not write the WAV file; aborting");
+
+    println!("{} frames of song {} recorded to {}", frames, player.song() as u16 + 1, output_path);
+}
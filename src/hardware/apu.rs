@@ -0,0 +1,847 @@
+/// The Audio Processing Unit (APU) Component
+///
+/// Four sound channels (two square channels, the first one with a frequency sweep, the wave
+/// channel and the noise channel) are mixed into two terminals (SO1/right and SO2/left).
+/// Registers are kept as written, and read back through the masks of their write-only bits.
+
+use super::memory_map;
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
+const CLOCK_SPEED            : u32   = 4_194_304; // T-cycles per second
+const FRAME_SEQUENCER_PERIOD : usize = 8192; // T-cycles; 512 Hz
+const T_CYCLES_PER_M_CYCLE   : usize = 4;
+// Charge factor of the high-pass filter capacitor (per T-cycle), which removes the DC offset
+const HIGH_PASS_CHARGE       : f64   = 0.999958;
+
+const SQUARE_LENGTH : u16 = 64;
+const WAVE_LENGTH   : u16 = 256;
+const NOISE_LENGTH  : u16 = 64;
+
+const DUTY_PATTERNS: [[u8; 8]; 4] = [
+    [0, 0, 0, 0, 0, 0, 0, 1], // 12.5%
+    [1, 0, 0, 0, 0, 0, 0, 1], // 25%
+    [1, 0, 0, 0, 0, 1, 1, 1], // 50%
+    [0, 1, 1, 1, 1, 1, 1, 0], // 75%
+];
+
+const NOISE_DIVISORS: [usize; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+/// Bits that always read as 1, for the 0xFF10-0xFF2F register range
+const READ_MASKS: [u8; 0x20] = [
+    0x80, 0x3F, 0x00, 0xFF, 0xBF, // NR10-NR14
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF, // (unused), NR21-NR24
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+    0xFF, 0xFF, 0x00, 0x00, 0xBF, // (unused), NR41-NR44
+    0x00, 0x00, 0x70,             // NR50-NR52
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // (unused)
+];
+
+/// Sound registers state after the DMG BOOT ROM hands over control to the cartridge
+const POST_BOOT_REGISTERS: [(u16, u8); 20] = [
+    (memory_map::NR10, 0x80),
+    (memory_map::NR11, 0xBF),
+    (memory_map::NR12, 0xF3),
+    (memory_map::NR13, 0xFF),
+    (memory_map::NR14, 0xBF),
+    (memory_map::NR21, 0x3F),
+    (memory_map::NR22, 0x00),
+    (memory_map::NR23, 0xFF),
+    (memory_map::NR24, 0xBF),
+    (memory_map::NR30, 0x7F),
+    (memory_map::NR31, 0xFF),
+    (memory_map::NR32, 0x9F),
+    (memory_map::NR33, 0xFF),
+    (memory_map::NR34, 0xBF),
+    (memory_map::NR41, 0xFF),
+    (memory_map::NR42, 0x00),
+    (memory_map::NR43, 0x00),
+    (memory_map::NR44, 0xBF),
+    (memory_map::NR50, 0x77),
+    (memory_map::NR51, 0xF3),
+];
+
+/// Length counter shared by all channels; it disables the channel when it runs out
+struct Length {
+    maximum: u16,
+    counter: u16,
+    enabled: bool,
+}
+
+impl Length {
+
+    fn new(maximum: u16) -> Length {
+        Length {
+            maximum: maximum,
+            counter: 0,
+            enabled: false,
+        }
+    }
+
+    fn load(&mut self, length: u16) {
+        self.counter = self.maximum - length;
+    }
+
+    /// Returns false when the counter has run out (the channel must be disabled)
+    fn clock(&mut self) -> bool {
+        if self.enabled && self.counter > 0 {
+            self.counter -= 1;
+            return self.counter > 0;
+        }
+        true
+    }
+
+    /// Handles NRx4 writes; `length_step` tells if the next frame sequencer step doesn't
+    /// clock the length counters, in which case enabling the counter clocks it once
+    fn write(&mut self, word: u8, length_step: bool) -> bool {
+        let was_enabled = self.enabled;
+        let mut active = true;
+        self.enabled = (word & 0x40) > 0;
+
+        if !was_enabled && self.enabled && length_step {
+            active = self.clock();
+        }
+
+        if (word & 0x80) > 0 && self.counter == 0 {
+            self.counter = self.maximum;
+            if self.enabled && length_step {
+                self.counter -= 1;
+            }
+        }
+
+        active
+    }
+
+}
+
+/// Volume envelope, used by the square and the noise channels
+struct Envelope {
+    initial_volume: u8,
+    increase: bool,
+    period: u8,
+    volume: u8,
+    timer: u8,
+}
+
+impl Envelope {
+
+    fn new() -> Envelope {
+        Envelope {
+            initial_volume: 0,
+            increase: false,
+            period: 0,
+            volume: 0,
+            timer: 0,
+        }
+    }
+
+    fn write(&mut self, word: u8) {
+        self.initial_volume = word >> 4;
+        self.increase = (word & 0x08) > 0;
+        self.period = word & 0x07;
+    }
+
+    /// The DAC is powered by any of the upper 5 bits of NRx2
+    fn dac_enabled(&self) -> bool {
+        self.initial_volume > 0 || self.increase
+    }
+
+    fn trigger(&mut self) {
+        self.volume = self.initial_volume;
+        self.timer = self.reload();
+    }
+
+    fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.reload();
+            match self.increase {
+                true  => if self.volume < 15 { self.volume += 1 },
+                false => if self.volume > 0 { self.volume -= 1 },
+            }
+        }
+    }
+
+    fn reload(&self) -> u8 {
+        match self.period {
+            0 => 8,
+            period => period,
+        }
+    }
+
+}
+
+/// Frequency sweep of the first square channel
+struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    shadow_frequency: u16,
+    timer: u8,
+    enabled: bool,
+    // A negate calculation happened since the last trigger
+    negate_used: bool,
+}
+
+impl Sweep {
+
+    fn new() -> Sweep {
+        Sweep {
+            period: 0,
+            negate: false,
+            shift: 0,
+            shadow_frequency: 0,
+            timer: 0,
+            enabled: false,
+            negate_used: false,
+        }
+    }
+
+    /// Returns false if clearing the negate mode after a negate calculation disables the channel
+    fn write(&mut self, word: u8) -> bool {
+        let negate = (word & 0x08) > 0;
+        let active = !(self.negate && !negate && self.negate_used);
+        self.period = (word >> 4) & 0x07;
+        self.negate = negate;
+        self.shift = word & 0x07;
+        active
+    }
+
+    fn reload(&self) -> u8 {
+        match self.period {
+            0 => 8,
+            period => period,
+        }
+    }
+
+    /// The next frequency; a value above 2047 disables the channel
+    fn calculate(&mut self) -> u16 {
+        let delta = self.shadow_frequency >> self.shift;
+        match self.negate {
+            true => {
+                self.negate_used = true;
+                self.shadow_frequency - delta
+            },
+            false => self.shadow_frequency + delta,
+        }
+    }
+
+}
+
+struct SquareChannel {
+    enabled: bool,
+    duty: u8,
+    duty_position: usize,
+    frequency: u16,
+    timer: usize,
+    length: Length,
+    envelope: Envelope,
+    sweep: Option<Sweep>,
+}
+
+impl SquareChannel {
+
+    fn new(has_sweep: bool) -> SquareChannel {
+        SquareChannel {
+            enabled: false,
+            duty: 0,
+            duty_position: 0,
+            frequency: 0,
+            timer: 0,
+            length: Length::new(SQUARE_LENGTH),
+            envelope: Envelope::new(),
+            sweep: match has_sweep {
+                true  => Some(Sweep::new()),
+                false => None,
+            },
+        }
+    }
+
+    fn period(&self) -> usize {
+        (2048 - self.frequency as usize) * 4
+    }
+
+    fn step(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period();
+            self.duty_position = (self.duty_position + 1) & 0x07;
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.timer = self.period();
+        self.envelope.trigger();
+
+        let frequency = self.frequency;
+        let mut overflow = false;
+        if let Some(ref mut sweep) = self.sweep {
+            sweep.shadow_frequency = frequency;
+            sweep.timer = sweep.reload();
+            sweep.enabled = sweep.period > 0 || sweep.shift > 0;
+            sweep.negate_used = false;
+            if sweep.shift > 0 {
+                overflow = sweep.calculate() > 2047;
+            }
+        }
+        if overflow {
+            self.enabled = false;
+        }
+    }
+
+    fn clock_sweep(&mut self) {
+        let mut frequency: Option<u16> = None;
+        let mut overflow = false;
+
+        if let Some(ref mut sweep) = self.sweep {
+            if sweep.timer > 0 {
+                sweep.timer -= 1;
+            }
+            if sweep.timer == 0 {
+                sweep.timer = sweep.reload();
+                if sweep.enabled && sweep.period > 0 {
+                    let next_frequency = sweep.calculate();
+                    if next_frequency > 2047 {
+                        overflow = true;
+                    } else if sweep.shift > 0 {
+                        sweep.shadow_frequency = next_frequency;
+                        frequency = Some(next_frequency);
+                        // The new frequency is checked for an overflow right away
+                        overflow = sweep.calculate() > 2047;
+                    }
+                }
+            }
+        }
+
+        if let Some(frequency) = frequency {
+            self.frequency = frequency;
+        }
+        if overflow {
+            self.enabled = false;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        match self.enabled {
+            true  => DUTY_PATTERNS[self.duty as usize][self.duty_position] * self.envelope.volume,
+            false => 0,
+        }
+    }
+
+}
+
+struct WaveChannel {
+    enabled: bool,
+    dac_enabled: bool,
+    volume_code: u8,
+    frequency: u16,
+    timer: usize,
+    position: usize,
+    // The sample being played (the wave RAM is read one byte at a time)
+    sample_buffer: u8,
+    length: Length,
+    ram: [u8; 16],
+}
+
+impl WaveChannel {
+
+    fn new() -> WaveChannel {
+        WaveChannel {
+            enabled: false,
+            dac_enabled: false,
+            volume_code: 0,
+            frequency: 0,
+            timer: 0,
+            position: 0,
+            sample_buffer: 0,
+            length: Length::new(WAVE_LENGTH),
+            ram: [0; 16],
+        }
+    }
+
+    fn period(&self) -> usize {
+        (2048 - self.frequency as usize) * 2
+    }
+
+    fn step(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period();
+            self.position = (self.position + 1) & 0x1F;
+            let byte = self.ram[self.position / 2];
+            self.sample_buffer = match self.position % 2 {
+                0 => byte >> 4,
+                _ => byte & 0x0F,
+            };
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        // The first sample played is the one left in the buffer; the position starts over
+        self.timer = self.period() + 6;
+        self.position = 0;
+    }
+
+    fn output(&self) -> u8 {
+        match (self.enabled, self.volume_code) {
+            (false, _) | (true, 0) => 0,
+            (true, code) => self.sample_buffer >> (code - 1),
+        }
+    }
+
+}
+
+struct NoiseChannel {
+    enabled: bool,
+    clock_shift: u8,
+    width_mode: bool,
+    divisor_code: u8,
+    timer: usize,
+    lfsr: u16,
+    length: Length,
+    envelope: Envelope,
+}
+
+impl NoiseChannel {
+
+    fn new() -> NoiseChannel {
+        NoiseChannel {
+            enabled: false,
+            clock_shift: 0,
+            width_mode: false,
+            divisor_code: 0,
+            timer: 0,
+            lfsr: 0x7FFF,
+            length: Length::new(NOISE_LENGTH),
+            envelope: Envelope::new(),
+        }
+    }
+
+    fn period(&self) -> usize {
+        NOISE_DIVISORS[self.divisor_code as usize] << self.clock_shift
+    }
+
+    fn step(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period();
+            let feedback = (self.lfsr ^ (self.lfsr >> 1)) & 0x01;
+            self.lfsr = (self.lfsr >> 1) | (feedback << 14);
+            if self.width_mode {
+                self.lfsr = (self.lfsr & !0x40) | (feedback << 6);
+            }
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.timer = self.period();
+        self.lfsr = 0x7FFF;
+        self.envelope.trigger();
+    }
+
+    fn output(&self) -> u8 {
+        match self.enabled {
+            true  => ((!self.lfsr & 0x01) as u8) * self.envelope.volume,
+            false => 0,
+        }
+    }
+
+}
+
+/// Downsamples the APU output (one value per T-cycle) to the host sample rate, averaging
+/// the output over each host sample period
+struct Resampler {
+    sample_rate: u32,
+    // Fractional position (in units of 1/CLOCK_SPEED) of the next host sample
+    position: u32,
+    left_sum: f32,
+    right_sum: f32,
+    summed_cycles: u32,
+    left_capacitor: f32,
+    right_capacitor: f32,
+    high_pass_charge: f32,
+    // Interleaved stereo samples (left, right), waiting to be taken by the frontend
+    samples: Vec<f32>,
+}
+
+impl Resampler {
+
+    fn new(sample_rate: u32) -> Resampler {
+        let cycles_per_sample: f64 = CLOCK_SPEED as f64 / sample_rate as f64;
+
+        Resampler {
+            sample_rate: sample_rate,
+            position: 0,
+            left_sum: 0.0,
+            right_sum: 0.0,
+            summed_cycles: 0,
+            left_capacitor: 0.0,
+            right_capacitor: 0.0,
+            high_pass_charge: HIGH_PASS_CHARGE.powf(cycles_per_sample) as f32,
+            samples: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, (left, right): (f32, f32)) {
+        self.left_sum += left;
+        self.right_sum += right;
+        self.summed_cycles += 1;
+
+        self.position += self.sample_rate;
+        if self.position >= CLOCK_SPEED {
+            self.position -= CLOCK_SPEED;
+
+            let left = self.left_sum / self.summed_cycles as f32;
+            let right = self.right_sum / self.summed_cycles as f32;
+            let left_output = left - self.left_capacitor;
+            let right_output = right - self.right_capacitor;
+            self.left_capacitor = left - left_output * self.high_pass_charge;
+            self.right_capacitor = right - right_output * self.high_pass_charge;
+            self.samples.push(left_output);
+            self.samples.push(right_output);
+
+            self.left_sum = 0.0;
+            self.right_sum = 0.0;
+            self.summed_cycles = 0;
+        }
+    }
+
+}
+
+pub struct APU {
+    powered: bool,
+    // Raw values of 0xFF10-0xFF2F, as written
+    registers: [u8; 0x20],
+    frame_sequencer_cycles: usize,
+    frame_sequencer_step: u8,
+    square1: SquareChannel,
+    square2: SquareChannel,
+    wave: WaveChannel,
+    noise: NoiseChannel,
+    resampler: Option<Resampler>,
+}
+
+impl Default for APU {
+
+    fn default() -> APU {
+        APU::new()
+    }
+
+}
+
+impl APU {
+
+    pub fn new() -> APU {
+        APU {
+            powered: false,
+            registers: [0; 0x20],
+            frame_sequencer_cycles: 0,
+            frame_sequencer_step: 0,
+            square1: SquareChannel::new(true),
+            square2: SquareChannel::new(false),
+            wave: WaveChannel::new(),
+            noise: NoiseChannel::new(),
+            resampler: None,
+        }
+    }
+
+    /// Starts producing samples at the host sample rate (e.g. 48000 Hz)
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.resampler = match sample_rate {
+            0 => None,
+            sample_rate => Some(Resampler::new(sample_rate)),
+        };
+    }
+
+    /// The host sample rate (0 when no samples are being produced)
+    pub fn sample_rate(&self) -> u32 {
+        match self.resampler {
+            Some(ref resampler) => resampler.sample_rate,
+            None => 0,
+        }
+    }
+
+    /// Takes the samples produced since the last call: interleaved stereo (left, right)
+    /// values, between -1.0 and 1.0
+    pub fn take_samples(&mut self) -> Vec<f32> {
+        match self.resampler {
+            Some(ref mut resampler) => std::mem::take(&mut resampler.samples),
+            None => Vec::new(),
+        }
+    }
+
+    /// Sets the sound registers to the values left by the DMG BOOT ROM: the first square
+    /// channel is still enabled, with its envelope faded out
+    pub fn post_boot(&mut self) {
+        self.write(memory_map::NR52, 0x80);
+        for &(address, word) in POST_BOOT_REGISTERS.iter() {
+            // Restarting the channels would replay the BOOT ROM sound
+            let word = match address {
+                memory_map::NR14 | memory_map::NR24 | memory_map::NR34 | memory_map::NR44 => word & 0x7F,
+                _ => word,
+            };
+            self.write(address, word);
+        }
+        self.square1.enabled = true;
+        self.square1.envelope.volume = 0;
+    }
+
+    pub fn read(&self, address: u16) -> u8 {
+        match address {
+            memory_map::NR52 => {
+                let mut word: u8 = READ_MASKS[(address - memory_map::NR10) as usize];
+                if self.powered { word |= 0x80 }
+                if self.square1.enabled { word |= 0x01 }
+                if self.square2.enabled { word |= 0x02 }
+                if self.wave.enabled { word |= 0x04 }
+                if self.noise.enabled { word |= 0x08 }
+                word
+            },
+            memory_map::NR10..=0xFF2F => {
+                let index = (address - memory_map::NR10) as usize;
+                self.registers[index] | READ_MASKS[index]
+            },
+            memory_map::WPR0..=memory_map::WPR9 => self.wave.ram[(address - memory_map::WPR0) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, address: u16, word: u8) {
+        match address {
+            memory_map::WPR0..=memory_map::WPR9 => {
+                self.wave.ram[(address - memory_map::WPR0) as usize] = word;
+                return;
+            },
+            memory_map::NR52 => {
+                let powered = (word & 0x80) > 0;
+                if self.powered && !powered {
+                    self.power_off();
+                } else if !self.powered && powered {
+                    self.frame_sequencer_cycles = 0;
+                    self.frame_sequencer_step = 0;
+                    self.square1.duty_position = 0;
+                    self.square2.duty_position = 0;
+                    self.wave.sample_buffer = 0;
+                }
+                self.powered = powered;
+                return;
+            },
+            memory_map::NR10..=0xFF2F => {},
+            _ => return,
+        }
+
+        // While powered off, only the length counters may be written (DMG)
+        if !self.powered {
+            match address {
+                memory_map::NR11 | memory_map::NR21 | memory_map::NR31 | memory_map::NR41 => {},
+                _ => return,
+            }
+        }
+
+        // The next step doesn't clock the length counters (the last one did)
+        let length_step = (self.frame_sequencer_step & 0x01) == 1;
+        let trigger = (word & 0x80) > 0;
+
+        match address {
+            memory_map::NR10 => {
+                if let Some(ref mut sweep) = self.square1.sweep {
+                    if !sweep.write(word) {
+                        self.square1.enabled = false;
+                    }
+                }
+            },
+            memory_map::NR11 => {
+                if self.powered { self.square1.duty = word >> 6 }
+                self.square1.length.load((word & 0x3F) as u16);
+            },
+            memory_map::NR12 => {
+                self.square1.envelope.write(word);
+                if !self.square1.envelope.dac_enabled() { self.square1.enabled = false }
+            },
+            memory_map::NR13 => {
+                self.square1.frequency = (self.square1.frequency & 0x0700) | word as u16;
+            },
+            memory_map::NR14 => {
+                self.square1.frequency = (self.square1.frequency & 0x00FF) | (((word & 0x07) as u16) << 8);
+                if !self.square1.length.write(word, length_step) && !trigger {
+                    self.square1.enabled = false;
+                }
+                if trigger { self.square1.trigger() }
+            },
+            memory_map::NR21 => {
+                if self.powered { self.square2.duty = word >> 6 }
+                self.square2.length.load((word & 0x3F) as u16);
+            },
+            memory_map::NR22 => {
+                self.square2.envelope.write(word);
+                if !self.square2.envelope.dac_enabled() { self.square2.enabled = false }
+            },
+            memory_map::NR23 => {
+                self.square2.frequency = (self.square2.frequency & 0x0700) | word as u16;
+            },
+            memory_map::NR24 => {
+                self.square2.frequency = (self.square2.frequency & 0x00FF) | (((word & 0x07) as u16) << 8);
+                if !self.square2.length.write(word, length_step) && !trigger {
+                    self.square2.enabled = false;
+                }
+                if trigger { self.square2.trigger() }
+            },
+            memory_map::NR30 => {
+                self.wave.dac_enabled = (word & 0x80) > 0;
+                if !self.wave.dac_enabled { self.wave.enabled = false }
+            },
+            memory_map::NR31 => {
+                self.wave.length.load(word as u16);
+            },
+            memory_map::NR32 => {
+                self.wave.volume_code = (word >> 5) & 0x03;
+            },
+            memory_map::NR33 => {
+                self.wave.frequency = (self.wave.frequency & 0x0700) | word as u16;
+            },
+            memory_map::NR34 => {
+                self.wave.frequency = (self.wave.frequency & 0x00FF) | (((word & 0x07) as u16) << 8);
+                if !self.wave.length.write(word, length_step) && !trigger {
+                    self.wave.enabled = false;
+                }
+                if trigger { self.wave.trigger() }
+            },
+            memory_map::NR41 => {
+                self.noise.length.load((word & 0x3F) as u16);
+            },
+            memory_map::NR42 => {
+                self.noise.envelope.write(word);
+                if !self.noise.envelope.dac_enabled() { self.noise.enabled = false }
+            },
+            memory_map::NR43 => {
+                self.noise.clock_shift = word >> 4;
+                self.noise.width_mode = (word & 0x08) > 0;
+                self.noise.divisor_code = word & 0x07;
+            },
+            memory_map::NR44 => {
+                if !self.noise.length.write(word, length_step) && !trigger {
+                    self.noise.enabled = false;
+                }
+                if trigger { self.noise.trigger() }
+            },
+            _ => {},
+        }
+
+        if self.powered {
+            self.registers[(address - memory_map::NR10) as usize] = word;
+        }
+    }
+
+    /// Advances the channels and the frame sequencer by the given amount of (machine) cycles
+    pub fn tick(&mut self, cycles: usize) {
+        if !self.powered && self.resampler.is_none() {
+            return;
+        }
+
+        for _ in 0..(cycles * T_CYCLES_PER_M_CYCLE) {
+            if self.powered {
+                self.frame_sequencer_cycles += 1;
+                if self.frame_sequencer_cycles >= FRAME_SEQUENCER_PERIOD {
+                    self.frame_sequencer_cycles = 0;
+                    self.clock_frame_sequencer();
+                }
+
+                self.square1.step();
+                self.square2.step();
+                self.wave.step();
+                self.noise.step();
+            }
+
+            // Silence is produced while powered off, so the host keeps its pace
+            if self.resampler.is_some() {
+                let output = self.output();
+                if let Some(ref mut resampler) = self.resampler {
+                    resampler.push(output);
+                }
+            }
+        }
+    }
+
+    /// Length counters are clocked at 256 Hz, the sweep at 128 Hz and the envelopes at 64 Hz
+    fn clock_frame_sequencer(&mut self) {
+        match self.frame_sequencer_step {
+            0 | 4 => self.clock_lengths(),
+            2 | 6 => {
+                self.clock_lengths();
+                self.square1.clock_sweep();
+            },
+            7 => {
+                self.square1.envelope.clock();
+                self.square2.envelope.clock();
+                self.noise.envelope.clock();
+            },
+            _ => {},
+        }
+        self.frame_sequencer_step = (self.frame_sequencer_step + 1) & 0x07;
+    }
+
+    fn clock_lengths(&mut self) {
+        if !self.square1.length.clock() { self.square1.enabled = false }
+        if !self.square2.length.clock() { self.square2.enabled = false }
+        if !self.wave.length.clock() { self.wave.enabled = false }
+        if !self.noise.length.clock() { self.noise.enabled = false }
+    }
+
+    /// Clears every sound register (but the wave RAM) and disables all channels
+    fn power_off(&mut self) {
+        let wave_ram = self.wave.ram;
+        self.registers = [0; 0x20];
+        self.square1 = SquareChannel::new(true);
+        self.square2 = SquareChannel::new(false);
+        self.wave = WaveChannel::new();
+        self.wave.ram = wave_ram;
+        self.noise = NoiseChannel::new();
+    }
+
+    /// The current output of each terminal (left/SO2 and right/SO1), between -1.0 and 1.0
+    pub fn output(&self) -> (f32, f32) {
+        if !self.powered {
+            return (0.0, 0.0);
+        }
+
+        let channels: [(u8, bool); 4] = [
+            (self.square1.output(), self.square1.envelope.dac_enabled()),
+            (self.square2.output(), self.square2.envelope.dac_enabled()),
+            (self.wave.output(), self.wave.dac_enabled),
+            (self.noise.output(), self.noise.envelope.dac_enabled()),
+        ];
+        let panning: u8 = self.registers[(memory_map::NR51 - memory_map::NR10) as usize];
+        let volume: u8 = self.registers[(memory_map::NR50 - memory_map::NR10) as usize];
+        let mut left: f32 = 0.0;
+        let mut right: f32 = 0.0;
+
+        for (index, &(digital, dac_enabled)) in channels.iter().enumerate() {
+            if !dac_enabled {
+                continue;
+            }
+            // Each DAC maps the digital 0x0-0xF range to an analog 1.0 to -1.0 range
+            let analog: f32 = 1.0 - (digital as f32) / 7.5;
+            if (panning & (0x10 << index)) > 0 { left += analog }
+            if (panning & (0x01 << index)) > 0 { right += analog }
+        }
+
+        let left_volume: f32 = (((volume >> 4) & 0x07) + 1) as f32 / 8.0;
+        let right_volume: f32 = ((volume & 0x07) + 1) as f32 / 8.0;
+        (left / 4.0 * left_volume, right / 4.0 * right_volume)
+    }
+
+}
@@ -0,0 +1,134 @@
+/// BOOT ROM (Internal ROM) for each GAME BOY model
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+
+use super::memory_map;
+
+const DMG_BOOT_ROM_SIZE : usize = 0x0100; // 256 bytes
+const CGB_BOOT_ROM_SIZE : usize = 0x0900; // 2304 bytes
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Model {
+    /// Early GAME BOY (DMG) revision
+    DMG0,
+    DMG,
+    /// GAME BOY Pocket
+    MGB,
+    /// Super GAME BOY
+    SGB,
+    SGB2,
+    /// Early GAME BOY Color revision
+    CGB0,
+    CGB,
+}
+
+impl Model {
+
+    pub fn from_name(name: &str) -> Option<Model> {
+        match name.to_uppercase().as_ref() {
+            "DMG0" => Some(Model::DMG0),
+            "DMG"  => Some(Model::DMG),
+            "MGB"  => Some(Model::MGB),
+            "SGB"  => Some(Model::SGB),
+            "SGB2" => Some(Model::SGB2),
+            "CGB0" => Some(Model::CGB0),
+            "CGB"  => Some(Model::CGB),
+            _      => None,
+        }
+    }
+
+    pub fn boot_rom_size(&self) -> usize {
+        match *self {
+            Model::CGB0 | Model::CGB => CGB_BOOT_ROM_SIZE,
+            _ => DMG_BOOT_ROM_SIZE,
+        }
+    }
+
+}
+
+#[derive(Debug)]
+pub enum BootRomError {
+    Io(io::Error),
+    /// The BOOT ROM size doesn't match the expected size for the model
+    InvalidSize(Model, usize),
+}
+
+impl fmt::Display for BootRomError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BootRomError::Io(ref error) => write!(f, "{}", error),
+            BootRomError::InvalidSize(model, size) => write!(f, "{:?} BOOT ROM must have {} bytes (found {} bytes)",
+                model, model.boot_rom_size(), size),
+        }
+    }
+
+}
+
+impl Error for BootRomError {}
+
+impl From<io::Error> for BootRomError {
+
+    fn from(error: io::Error) -> BootRomError {
+        BootRomError::Io(error)
+    }
+
+}
+
+/// The DMG, MGB and SGB BOOT ROMs (256 bytes) are mapped at 0000-00FF. The CGB BOOT ROM
+/// (2304 bytes) is mapped at 0000-00FF and 0200-08FF; the gap at 0100-01FF keeps the
+/// cartridge header visible, so the BOOT ROM can read it.
+pub struct BootRom {
+    model: Model,
+    data: Vec<u8>,
+}
+
+impl BootRom {
+
+    pub fn from_bytes(data: Vec<u8>, model: Model) -> Result<BootRom, BootRomError> {
+        if data.len() != model.boot_rom_size() {
+            return Err(BootRomError::InvalidSize(model, data.len()));
+        }
+
+        Ok(BootRom {
+            model: model,
+            data: data,
+        })
+    }
+
+    pub fn from_file(file_path: &str, model: Model) -> Result<BootRom, BootRomError> {
+        let mut boot_rom_file = File::open(file_path)?;
+        let mut boot_rom_buffer: Vec<u8> = Vec::new();
+        boot_rom_file.read_to_end(&mut boot_rom_buffer)?;
+        BootRom::from_bytes(boot_rom_buffer, model)
+    }
+
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Whether the address is served by the BOOT ROM (while it is enabled)
+    pub fn maps(&self, address: u16) -> bool {
+        match address {
+            memory_map::IROM..=memory_map::IROX => true,
+            memory_map::BGN..=0x01FF => false,
+            _ => (address as usize) < self.data.len(),
+        }
+    }
+
+    pub fn read(&self, address: u16) -> u8 {
+        self.data[address as usize]
+    }
+
+    /// Overwrites a byte (used by the debugging tools)
+    pub fn patch(&mut self, address: u16, word: u8) {
+        if (address as usize) < self.data.len() {
+            self.data[address as usize] = word;
+        }
+    }
+
+}
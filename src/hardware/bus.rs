@@ -0,0 +1,109 @@
+/// The Memory Bus as seen by the CPU
+
+use super::memory::Memory;
+use super::timer::Timer;
+use super::ppu::PPU;
+
+const T_CYCLES_PER_M_CYCLE : usize = 4;
+
+/// The CPU reaches the memory through `cpu_fetch` and `cpu_write`, which are subject to the
+/// access restrictions (and may take a machine cycle each); `fetch` and `write` are the
+/// unrestricted, instant accesses (e.g. IE and IF while checking the interrupts)
+pub trait Bus {
+    fn cpu_fetch(&mut self, address: u16) -> u8;
+    fn cpu_write(&mut self, address: u16, word: u8);
+    fn fetch(&mut self, address: u16) -> u8;
+    fn write(&mut self, address: u16, word: u8);
+}
+
+/// A bare Memory Bus: the CPU accesses don't run the rest of the system
+impl Bus for Memory {
+
+    fn cpu_fetch(&mut self, address: u16) -> u8 {
+        Memory::cpu_fetch(self, address)
+    }
+
+    fn cpu_write(&mut self, address: u16, word: u8) {
+        Memory::cpu_write(self, address, word);
+    }
+
+    fn fetch(&mut self, address: u16) -> u8 {
+        Memory::fetch(self, address)
+    }
+
+    fn write(&mut self, address: u16, word: u8) {
+        Memory::write(self, address, word);
+    }
+
+}
+
+/// The Memory Bus along with the components that run on the same clock, borrowed for an
+/// instruction. Each CPU access takes a machine cycle, in which the rest of the system runs
+/// (4 T-cycles) before the access is made
+pub struct SystemBus<'a> {
+    memory: &'a mut Memory,
+    timer: &'a mut Timer,
+    ppu: Option<&'a mut PPU>,
+    // Machine cycles of the current instruction spent on memory accesses
+    access_cycles: usize,
+}
+
+impl<'a> SystemBus<'a> {
+
+    pub fn new(memory: &'a mut Memory, timer: &'a mut Timer, ppu: Option<&'a mut PPU>) -> SystemBus<'a> {
+        SystemBus {
+            memory: memory,
+            timer: timer,
+            ppu: ppu,
+            access_cycles: 0,
+        }
+    }
+
+    /// Runs what's left of an instruction that took `cycles` machine cycles (its internal
+    /// operations, after the memory accesses). Returns the machine cycles it took
+    pub fn complete_instruction(mut self, cycles: usize) -> usize {
+        let access_cycles: usize = self.access_cycles;
+        if cycles > access_cycles {
+            self.advance(cycles - access_cycles);
+        }
+        cycles.max(access_cycles)
+    }
+
+    /// Advances the components of the Memory Bus (see `Memory::tick`), the timer and the
+    /// PPU (if any) by machine cycles
+    pub fn advance(&mut self, cycles: usize) {
+        self.memory.tick(cycles);
+        self.timer.updater(self.memory, cycles * T_CYCLES_PER_M_CYCLE);
+        if let Some(ref mut ppu) = self.ppu {
+            ppu.cycle(self.memory, cycles);
+        }
+    }
+
+    fn access_cycle(&mut self) {
+        self.access_cycles += 1;
+        self.advance(1);
+    }
+
+}
+
+impl<'a> Bus for SystemBus<'a> {
+
+    fn cpu_fetch(&mut self, address: u16) -> u8 {
+        self.access_cycle();
+        self.memory.cpu_fetch(address)
+    }
+
+    fn cpu_write(&mut self, address: u16, word: u8) {
+        self.access_cycle();
+        self.memory.cpu_write(address, word);
+    }
+
+    fn fetch(&mut self, address: u16) -> u8 {
+        self.memory.fetch(address)
+    }
+
+    fn write(&mut self, address: u16, word: u8) {
+        self.memory.write(address, word);
+    }
+
+}
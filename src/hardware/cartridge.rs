@@ -0,0 +1,206 @@
+/// Cartridge: the ROM and the Memory Bank Controller (MBC), if any
+
+use super::memory_map;
+use super::cartridge_types;
+use super::rtc::{RealTimeClock, SystemClock};
+use super::mbc1::MBC1;
+use super::mbc2::MBC2;
+use super::mbc3::MBC3;
+use super::mbc5::MBC5;
+
+pub const ROM_BANK_SIZE: usize = 0x4000; // 16kB
+pub const RAM_BANK_SIZE: usize = 0x2000; // 8kB
+
+/// Every Memory Bank Controller (and the plain ROM) implements this trait. The Memory
+/// Bus forwards the ROM area (0000-7FFF) and the External RAM area (A000-BFFF) to it.
+pub trait Cartridge {
+    /// Reads from the ROM area (0000-7FFF), according to the selected banks
+    fn read_rom(&self, address: u16) -> u8;
+
+    /// Writes to the ROM area (0000-7FFF) are caught by the MBC registers
+    fn write_rom(&mut self, address: u16, word: u8);
+
+    /// Reads from the External RAM area (A000-BFFF); 0xFF if disabled or absent
+    fn read_ram(&self, address: u16) -> u8;
+
+    /// Writes to the External RAM area (A000-BFFF); returns whether the battery-backed data
+    /// changed (a byte of the RAM, or an RTC register). Writes while it's disabled don't count
+    fn write_ram(&mut self, address: u16, word: u8) -> bool;
+
+    /// Data kept by the cartridge battery (the External RAM, and the RTC if any)
+    fn save_data(&self) -> Vec<u8>;
+
+    /// Restores the data previously obtained through `save_data`
+    fn load_data(&mut self, data: &[u8]);
+
+    /// Advances the cartridge by the given amount of CPU cycles
+    fn tick(&mut self, _cycles: usize) {}
+
+    /// Whether the cartridge is requesting its rumble motor to be active
+    fn rumble_active(&self) -> bool {
+        false
+    }
+}
+
+/// Cartridge without a Memory Bank Controller: 32kB of ROM and (optionally) 8kB of RAM
+pub struct RomOnly {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+}
+
+impl RomOnly {
+
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> RomOnly {
+        RomOnly {
+            rom: rom,
+            ram: vec![0; ram_size],
+        }
+    }
+
+}
+
+impl Cartridge for RomOnly {
+
+    fn read_rom(&self, address: u16) -> u8 {
+        match address {
+            memory_map::ROM0..=memory_map::ROM9 => read_bank(&self.rom, 0, address),
+            _ => read_bank(&self.rom, 1, address),
+        }
+    }
+
+    fn write_rom(&mut self, _address: u16, _word: u8) {}
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match ram_offset(&self.ram, 0, address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, word: u8) -> bool {
+        match ram_offset(&self.ram, 0, address) {
+            Some(offset) => store(&mut self.ram, offset, word),
+            None => false,
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    fn load_data(&mut self, data: &[u8]) {
+        load_ram(&mut self.ram, data);
+    }
+
+}
+
+/// Creates the cartridge according to the ROM Cartridge Type at the header. Unknown
+/// types fall back to a plain ROM.
+pub fn from_rom(rom: Vec<u8>) -> Box<dyn Cartridge> {
+    let cartridge_type: u8 = header_byte(&rom, memory_map::RTC);
+    let ram_size: usize = ram_size(header_byte(&rom, memory_map::ASIZ));
+
+    match cartridge_type {
+        cartridge_types::ROM_MBC1 |
+        cartridge_types::ROM_MBC1_RAM0 |
+        cartridge_types::ROM_MBC1_RAM1 => Box::new(MBC1::new(rom, ram_size)),
+        cartridge_types::ROM_MBC2 |
+        cartridge_types::ROM_MBC2_BATTERY => Box::new(MBC2::new(rom)),
+        cartridge_types::ROM_MBC3_TIMER_BATTERY |
+        cartridge_types::ROM_MBC3_TIMER_RAM_BATTERY => {
+            Box::new(MBC3::new(rom, ram_size, Some(RealTimeClock::new(Box::new(SystemClock)))))
+        },
+        cartridge_types::ROM_MBC3 |
+        cartridge_types::ROM_MBC3_RAM |
+        cartridge_types::ROM_MBC3_RAM_BATTERY => Box::new(MBC3::new(rom, ram_size, None)),
+        cartridge_types::ROM_MBC5 |
+        cartridge_types::ROM_MBC5_RAM |
+        cartridge_types::ROM_MBC5_RAM_BATTERY => Box::new(MBC5::new(rom, ram_size, false)),
+        cartridge_types::ROM_MBC5_RUMBLE |
+        cartridge_types::ROM_MBC5_RUMBLE_RAM |
+        cartridge_types::ROM_MBC5_RUMBLE_RAM_BATTERY => Box::new(MBC5::new(rom, ram_size, true)),
+        _ => Box::new(RomOnly::new(rom, ram_size)),
+    }
+}
+
+/// Whether the cartridge type has a Memory Bank Controller implementation
+pub fn is_supported(cartridge_type: u8) -> bool {
+    match cartridge_type {
+        cartridge_types::ROM_ONLY |
+        cartridge_types::ROM_RAM |
+        cartridge_types::ROM_RAM_BATTERY |
+        cartridge_types::ROM_MBC1 |
+        cartridge_types::ROM_MBC1_RAM0 |
+        cartridge_types::ROM_MBC1_RAM1 |
+        cartridge_types::ROM_MBC2 |
+        cartridge_types::ROM_MBC2_BATTERY |
+        cartridge_types::ROM_MBC3_TIMER_BATTERY |
+        cartridge_types::ROM_MBC3_TIMER_RAM_BATTERY |
+        cartridge_types::ROM_MBC3 |
+        cartridge_types::ROM_MBC3_RAM |
+        cartridge_types::ROM_MBC3_RAM_BATTERY |
+        cartridge_types::ROM_MBC5 |
+        cartridge_types::ROM_MBC5_RAM |
+        cartridge_types::ROM_MBC5_RAM_BATTERY |
+        cartridge_types::ROM_MBC5_RUMBLE |
+        cartridge_types::ROM_MBC5_RUMBLE_RAM |
+        cartridge_types::ROM_MBC5_RUMBLE_RAM_BATTERY => true,
+        _ => false,
+    }
+}
+
+/// Reads from a 16kB ROM bank; banks beyond the ROM size are mirrored
+pub fn read_bank(rom: &[u8], bank: usize, address: u16) -> u8 {
+    let banks: usize = rom_banks(rom);
+    let offset: usize = (bank % banks) * ROM_BANK_SIZE + (address as usize & (ROM_BANK_SIZE - 1));
+    match rom.get(offset) {
+        Some(word) => *word,
+        None => 0xFF,
+    }
+}
+
+/// Offset of an External RAM address (A000-BFFF) for a given 8kB RAM bank; RAM
+/// smaller than the addressable area is mirrored
+pub fn ram_offset(ram: &[u8], bank: usize, address: u16) -> Option<usize> {
+    if ram.is_empty() {
+        return None;
+    }
+    let offset: usize = bank * RAM_BANK_SIZE + (address.wrapping_sub(memory_map::SWR0) as usize & (RAM_BANK_SIZE - 1));
+    Some(offset % ram.len())
+}
+
+/// Stores a byte of RAM; returns whether it changed
+pub fn store(ram: &mut [u8], offset: usize, word: u8) -> bool {
+    let changed: bool = ram[offset] != word;
+    ram[offset] = word;
+    changed
+}
+
+pub fn load_ram(ram: &mut [u8], data: &[u8]) {
+    let length = if data.len() < ram.len() { data.len() } else { ram.len() };
+    ram[..length].copy_from_slice(&data[..length]);
+}
+
+pub fn rom_banks(rom: &[u8]) -> usize {
+    let banks = rom.len() / ROM_BANK_SIZE;
+    if banks == 0 { 1 } else { banks }
+}
+
+/// External RAM size, according to the RAM Size byte at the cartridge header
+pub fn ram_size(code: u8) -> usize {
+    match code {
+        0x01 => 0x0800,  // 2kB
+        0x02 => 0x2000,  // 8kB
+        0x03 => 0x8000,  // 32kB (4 banks of 8kB)
+        0x04 => 0x20000, // 128kB (16 banks of 8kB)
+        0x05 => 0x10000, // 64kB (8 banks of 8kB)
+        _    => 0,
+    }
+}
+
+fn header_byte(rom: &[u8], address: u16) -> u8 {
+    match rom.get(address as usize) {
+        Some(word) => *word,
+        None => 0x00,
+    }
+}
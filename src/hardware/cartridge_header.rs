@@ -0,0 +1,405 @@
+/// Cartridge Header (0x0100-0x014F) Parser & Validator
+
+use std::error::Error;
+use std::fmt;
+
+use super::memory_map;
+use super::cartridge;
+use super::cartridge_types;
+
+/// The Nintendo logo (0x0104-0x0133), checked by the BOOT ROM before starting the game
+pub const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83,
+    0x00, 0x0C, 0x00, 0x0D, 0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E,
+    0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99, 0xBB, 0xBB, 0x67, 0x63,
+    0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+/// Old Licensee Code stating that the New Licensee Code (0x0144-0x0145) must be used
+const USE_NEW_LICENSEE: u8 = 0x33;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorSupport {
+    /// GAME BOY (DMG) only game
+    None,
+    /// Supports CGB functions, but also works on the GAME BOY (0x80)
+    Supported,
+    /// Works on the CGB only (0xC0)
+    Only,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Destination {
+    Japan,
+    Overseas,
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HeaderError {
+    /// The ROM is not large enough to hold a header; the actual size is attached
+    TooShort(usize),
+}
+
+impl fmt::Display for HeaderError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeaderError::TooShort(size) => write!(f, "ROM too short to hold a cartridge header ({} bytes)", size),
+        }
+    }
+
+}
+
+impl Error for HeaderError {}
+
+#[derive(Debug, Clone)]
+pub struct CartridgeHeader {
+    pub title: String,
+    pub color_support: ColorSupport,
+    pub sgb_support: bool,
+    pub new_licensee_code: String,
+    pub old_licensee_code: u8,
+    pub cartridge_type: u8,
+    pub rom_size_code: u8,
+    pub ram_size_code: u8,
+    pub destination: Destination,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub computed_header_checksum: u8,
+    pub global_checksum: u16,
+    pub computed_global_checksum: u16,
+    pub logo_matches: bool,
+}
+
+impl CartridgeHeader {
+
+    pub fn parse(rom: &[u8]) -> Result<CartridgeHeader, HeaderError> {
+        if rom.len() <= memory_map::CSU9 as usize {
+            return Err(HeaderError::TooShort(rom.len()));
+        }
+
+        let byte = |address: u16| rom[address as usize];
+
+        let color_support: ColorSupport = match byte(memory_map::COLO) {
+            0x80 => ColorSupport::Supported,
+            0xC0 => ColorSupport::Only,
+            _    => ColorSupport::None,
+        };
+
+        // The title uses the CGB flag byte as well on older (DMG) cartridges
+        let title_end: u16 = match color_support {
+            ColorSupport::None => memory_map::COLO,
+            _ => memory_map::TN9,
+        };
+        let title: String = rom[(memory_map::TN0 as usize)..=(title_end as usize)].iter()
+            .take_while(|character| **character != 0x00)
+            .map(|character| if character.is_ascii_graphic() || *character == b' ' { *character as char } else { '?' })
+            .collect::<String>()
+            .trim_end()
+            .to_owned();
+
+        let new_licensee_code: String = [byte(memory_map::HLIC), byte(memory_map::LLIC)].iter()
+            .map(|character| *character as char)
+            .collect();
+
+        let destination: Destination = match byte(memory_map::DCOD) {
+            0x00 => Destination::Japan,
+            0x01 => Destination::Overseas,
+            code => Destination::Unknown(code),
+        };
+
+        Ok(CartridgeHeader {
+            title: title,
+            color_support: color_support,
+            sgb_support: byte(memory_map::GBV) == 0x03,
+            new_licensee_code: new_licensee_code,
+            old_licensee_code: byte(memory_map::LCOD),
+            cartridge_type: byte(memory_map::RTC),
+            rom_size_code: byte(memory_map::OSIZ),
+            ram_size_code: byte(memory_map::ASIZ),
+            destination: destination,
+            version: byte(memory_map::MROM),
+            header_checksum: byte(memory_map::CHEK),
+            computed_header_checksum: header_checksum(rom),
+            global_checksum: ((byte(memory_map::CSU0) as u16) << 8) | byte(memory_map::CSU9) as u16,
+            computed_global_checksum: global_checksum(rom),
+            logo_matches: rom[(memory_map::NTD0 as usize)..=(memory_map::NTD9 as usize)] == NINTENDO_LOGO[..],
+        })
+    }
+
+    /// Whether the BOOT ROM would accept the cartridge (logo and complement check)
+    pub fn is_valid(&self) -> bool {
+        self.logo_matches && self.header_checksum_matches()
+    }
+
+    pub fn header_checksum_matches(&self) -> bool {
+        self.header_checksum == self.computed_header_checksum
+    }
+
+    /// The global checksum is not verified by the hardware
+    pub fn global_checksum_matches(&self) -> bool {
+        self.global_checksum == self.computed_global_checksum
+    }
+
+    pub fn cartridge_type_name(&self) -> &'static str {
+        cartridge_types::name(self.cartridge_type)
+    }
+
+    /// ROM size in bytes: 32kB shifted left by the ROM Size code
+    pub fn rom_size(&self) -> usize {
+        match self.rom_size_code {
+            0x00..=0x08 => (32 * 1024) << self.rom_size_code,
+            _ => 0,
+        }
+    }
+
+    pub fn rom_banks(&self) -> usize {
+        self.rom_size() / cartridge::ROM_BANK_SIZE
+    }
+
+    pub fn ram_size(&self) -> usize {
+        cartridge::ram_size(self.ram_size_code)
+    }
+
+    pub fn licensee(&self) -> &'static str {
+        match self.old_licensee_code {
+            USE_NEW_LICENSEE => new_licensee(&self.new_licensee_code),
+            code => old_licensee(code),
+        }
+    }
+
+}
+
+/// x = 0; for each byte in 0x0134-0x014C: x = x - byte - 1
+pub fn header_checksum(rom: &[u8]) -> u8 {
+    rom[(memory_map::TN0 as usize)..=(memory_map::MROM as usize)].iter()
+        .fold(0u8, |checksum, byte| checksum.wrapping_sub(*byte).wrapping_sub(1))
+}
+
+/// The sum of every byte in the ROM, except the two global checksum bytes
+pub fn global_checksum(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|&(address, _)| address != memory_map::CSU0 as usize && address != memory_map::CSU9 as usize)
+        .fold(0u16, |checksum, (_, byte)| checksum.wrapping_add(*byte as u16))
+}
+
+fn new_licensee(code: &str) -> &'static str {
+    match code {
+        "00" => "None",
+        "01" => "Nintendo R&D1",
+        "08" => "Capcom",
+        "13" => "Electronic Arts",
+        "18" => "Hudson Soft",
+        "19" => "b-ai",
+        "20" => "kss",
+        "22" => "pow",
+        "24" => "PCM Complete",
+        "25" => "san-x",
+        "28" => "Kemco Japan",
+        "29" => "seta",
+        "30" => "Viacom",
+        "31" => "Nintendo",
+        "32" => "Bandai",
+        "33" => "Ocean/Acclaim",
+        "34" => "Konami",
+        "35" => "Hector",
+        "37" => "Taito",
+        "38" => "Hudson",
+        "39" => "Banpresto",
+        "41" => "Ubi Soft",
+        "42" => "Atlus",
+        "44" => "Malibu",
+        "46" => "angel",
+        "47" => "Bullet-Proof",
+        "49" => "irem",
+        "50" => "Absolute",
+        "51" => "Acclaim",
+        "52" => "Activision",
+        "53" => "American sammy",
+        "54" => "Konami",
+        "55" => "Hi tech entertainment",
+        "56" => "LJN",
+        "57" => "Matchbox",
+        "58" => "Mattel",
+        "59" => "Milton Bradley",
+        "60" => "Titus",
+        "61" => "Virgin",
+        "64" => "LucasArts",
+        "67" => "Ocean",
+        "69" => "Electronic Arts",
+        "70" => "Infogrames",
+        "71" => "Interplay",
+        "72" => "Broderbund",
+        "73" => "sculptured",
+        "75" => "sci",
+        "78" => "THQ",
+        "79" => "Accolade",
+        "80" => "misawa",
+        "83" => "lozc",
+        "86" => "Tokuma Shoten Intermedia",
+        "87" => "Tsukuda Original",
+        "91" => "Chunsoft",
+        "92" => "Video system",
+        "93" => "Ocean/Acclaim",
+        "95" => "Varie",
+        "96" => "Yonezawa/s'pal",
+        "97" => "Kaneko",
+        "99" => "Pack in soft",
+        "A4" => "Konami (Yu-Gi-Oh!)",
+        _    => "Unknown",
+    }
+}
+
+fn old_licensee(code: u8) -> &'static str {
+    match code {
+        0x00 => "None",
+        0x01 => "Nintendo",
+        0x08 => "Capcom",
+        0x09 => "Hot-B",
+        0x0A => "Jaleco",
+        0x0B => "Coconuts Japan",
+        0x0C => "Elite Systems",
+        0x13 => "Electronic Arts",
+        0x18 => "Hudson Soft",
+        0x19 => "ITC Entertainment",
+        0x1A => "Yanoman",
+        0x1D => "Japan Clary",
+        0x1F => "Virgin Interactive",
+        0x24 => "PCM Complete",
+        0x25 => "San-X",
+        0x28 => "Kotobuki Systems",
+        0x29 => "Seta",
+        0x30 => "Infogrames",
+        0x31 => "Nintendo",
+        0x32 => "Bandai",
+        0x34 => "Konami",
+        0x35 => "HectorSoft",
+        0x38 => "Capcom",
+        0x39 => "Banpresto",
+        0x3C => "Entertainment i",
+        0x3E => "Gremlin",
+        0x41 => "Ubisoft",
+        0x42 => "Atlus",
+        0x44 => "Malibu",
+        0x46 => "Angel",
+        0x47 => "Spectrum Holoby",
+        0x49 => "Irem",
+        0x4A => "Virgin Interactive",
+        0x4D => "Malibu",
+        0x4F => "U.S. Gold",
+        0x50 => "Absolute",
+        0x51 => "Acclaim",
+        0x52 => "Activision",
+        0x53 => "American Sammy",
+        0x54 => "GameTek",
+        0x55 => "Park Place",
+        0x56 => "LJN",
+        0x57 => "Matchbox",
+        0x59 => "Milton Bradley",
+        0x5A => "Mindscape",
+        0x5B => "Romstar",
+        0x5C => "Naxat Soft",
+        0x5D => "Tradewest",
+        0x60 => "Titus",
+        0x61 => "Virgin Interactive",
+        0x67 => "Ocean Interactive",
+        0x69 => "Electronic Arts",
+        0x6E => "Elite Systems",
+        0x6F => "Electro Brain",
+        0x70 => "Infogrames",
+        0x71 => "Interplay",
+        0x72 => "Broderbund",
+        0x73 => "Sculptered Soft",
+        0x75 => "The Sales Curve",
+        0x78 => "THQ",
+        0x79 => "Accolade",
+        0x7A => "Triffix Entertainment",
+        0x7C => "Microprose",
+        0x7F => "Kemco",
+        0x80 => "Misawa Entertainment",
+        0x83 => "Lozc",
+        0x86 => "Tokuma Shoten Intermedia",
+        0x8B => "Bullet-Proof Software",
+        0x8C => "Vic Tokai",
+        0x8E => "Ape",
+        0x8F => "I'Max",
+        0x91 => "Chunsoft",
+        0x92 => "Video System",
+        0x93 => "Tsubaraya Productions",
+        0x95 => "Varie",
+        0x96 => "Yonezawa/S'Pal",
+        0x97 => "Kaneko",
+        0x99 => "Arc",
+        0x9A => "Nihon Bussan",
+        0x9B => "Tecmo",
+        0x9C => "Imagineer",
+        0x9D => "Banpresto",
+        0x9F => "Nova",
+        0xA1 => "Hori Electric",
+        0xA2 => "Bandai",
+        0xA4 => "Konami",
+        0xA6 => "Kawada",
+        0xA7 => "Takara",
+        0xA9 => "Technos Japan",
+        0xAA => "Broderbund",
+        0xAC => "Toei Animation",
+        0xAD => "Toho",
+        0xAF => "Namco",
+        0xB0 => "Acclaim",
+        0xB1 => "ASCII or Nexsoft",
+        0xB2 => "Bandai",
+        0xB4 => "Square Enix",
+        0xB6 => "HAL Laboratory",
+        0xB7 => "SNK",
+        0xB9 => "Pony Canyon",
+        0xBA => "Culture Brain",
+        0xBB => "Sunsoft",
+        0xBD => "Sony Imagesoft",
+        0xBF => "Sammy",
+        0xC0 => "Taito",
+        0xC2 => "Kemco",
+        0xC3 => "Squaresoft",
+        0xC4 => "Tokuma Shoten Intermedia",
+        0xC5 => "Data East",
+        0xC6 => "Tonkinhouse",
+        0xC8 => "Koei",
+        0xC9 => "UFL",
+        0xCA => "Ultra",
+        0xCB => "Vap",
+        0xCC => "Use Corporation",
+        0xCD => "Meldac",
+        0xCE => "Pony Canyon",
+        0xCF => "Angel",
+        0xD0 => "Taito",
+        0xD1 => "Sofel",
+        0xD2 => "Quest",
+        0xD3 => "Sigma Enterprises",
+        0xD4 => "ASK Kodansha",
+        0xD6 => "Naxat Soft",
+        0xD7 => "Copya System",
+        0xD9 => "Banpresto",
+        0xDA => "Tomy",
+        0xDB => "LJN",
+        0xDD => "NCS",
+        0xDE => "Human",
+        0xDF => "Altron",
+        0xE0 => "Jaleco",
+        0xE1 => "Towa Chiki",
+        0xE2 => "Yutaka",
+        0xE3 => "Varie",
+        0xE5 => "Epoch",
+        0xE7 => "Athena",
+        0xE8 => "Asmik ACE Entertainment",
+        0xE9 => "Natsume",
+        0xEA => "King Records",
+        0xEB => "Atlus",
+        0xEC => "Epic/Sony Records",
+        0xEE => "IGS",
+        0xF0 => "A Wave",
+        0xF3 => "Extreme Entertainment",
+        0xFF => "LJN",
+        _    => "Unknown",
+    }
+}
@@ -1,6 +1,71 @@
 /// Supported ROM Cartridge Types
 
-pub const ROM_ONLY      : u8 = 0x00;
-pub const ROM_MBC1      : u8 = 0x01;
-pub const ROM_MBC1_RAM0 : u8 = 0x02;
-pub const ROM_MBC1_RAM1 : u8 = 0x03;
+pub const ROM_ONLY                   : u8 = 0x00;
+pub const ROM_MBC1                   : u8 = 0x01;
+pub const ROM_MBC1_RAM0              : u8 = 0x02;
+pub const ROM_MBC1_RAM1              : u8 = 0x03;
+pub const ROM_MBC2                   : u8 = 0x05;
+pub const ROM_MBC2_BATTERY           : u8 = 0x06;
+pub const ROM_RAM                    : u8 = 0x08;
+pub const ROM_RAM_BATTERY            : u8 = 0x09;
+pub const ROM_MBC3_TIMER_BATTERY     : u8 = 0x0F;
+pub const ROM_MBC3_TIMER_RAM_BATTERY : u8 = 0x10;
+pub const ROM_MBC3                   : u8 = 0x11;
+pub const ROM_MBC3_RAM               : u8 = 0x12;
+pub const ROM_MBC3_RAM_BATTERY       : u8 = 0x13;
+pub const ROM_MBC5                   : u8 = 0x19;
+pub const ROM_MBC5_RAM               : u8 = 0x1A;
+pub const ROM_MBC5_RAM_BATTERY       : u8 = 0x1B;
+pub const ROM_MBC5_RUMBLE            : u8 = 0x1C;
+pub const ROM_MBC5_RUMBLE_RAM        : u8 = 0x1D;
+pub const ROM_MBC5_RUMBLE_RAM_BATTERY: u8 = 0x1E;
+
+/// Whether the cartridge keeps its External RAM (or RTC) powered by a battery
+pub fn has_battery(cartridge_type: u8) -> bool {
+    match cartridge_type {
+        ROM_MBC1_RAM1 |
+        ROM_MBC2_BATTERY |
+        ROM_RAM_BATTERY |
+        ROM_MBC3_TIMER_BATTERY |
+        ROM_MBC3_TIMER_RAM_BATTERY |
+        ROM_MBC3_RAM_BATTERY |
+        ROM_MBC5_RAM_BATTERY |
+        ROM_MBC5_RUMBLE_RAM_BATTERY => true,
+        _ => false,
+    }
+}
+
+/// Name of the cartridge type, as described at the cartridge header (0x0147)
+pub fn name(cartridge_type: u8) -> &'static str {
+    match cartridge_type {
+        0x00 => "ROM ONLY",
+        0x01 => "MBC1",
+        0x02 => "MBC1+RAM",
+        0x03 => "MBC1+RAM+BATTERY",
+        0x05 => "MBC2",
+        0x06 => "MBC2+BATTERY",
+        0x08 => "ROM+RAM",
+        0x09 => "ROM+RAM+BATTERY",
+        0x0B => "MMM01",
+        0x0C => "MMM01+RAM",
+        0x0D => "MMM01+RAM+BATTERY",
+        0x0F => "MBC3+TIMER+BATTERY",
+        0x10 => "MBC3+TIMER+RAM+BATTERY",
+        0x11 => "MBC3",
+        0x12 => "MBC3+RAM",
+        0x13 => "MBC3+RAM+BATTERY",
+        0x19 => "MBC5",
+        0x1A => "MBC5+RAM",
+        0x1B => "MBC5+RAM+BATTERY",
+        0x1C => "MBC5+RUMBLE",
+        0x1D => "MBC5+RUMBLE+RAM",
+        0x1E => "MBC5+RUMBLE+RAM+BATTERY",
+        0x20 => "MBC6",
+        0x22 => "MBC7+SENSOR+RUMBLE+RAM+BATTERY",
+        0xFC => "POCKET CAMERA",
+        0xFD => "BANDAI TAMA5",
+        0xFE => "HuC3",
+        0xFF => "HuC1+RAM+BATTERY",
+        _    => "UNKNOWN",
+    }
+}
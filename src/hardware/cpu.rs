@@ -1,6 +1,7 @@
 /// The Central Processing Unit
 
-use super::memory::Memory;
+use super::bus::Bus;
+use super::memory_map;
 use super::instruction_set;
 use super::interrupt;
 
@@ -143,6 +144,7 @@ impl CPU {
         self.registers.instruction_register = 0x00;
     }
 
+    /// The registers state after the DMG BOOT ROM hands over control to the cartridge
     pub fn boot_expected(&mut self) {
         self.registers.r_a = 0x01;
         self.registers.r_f = 0xB0;
@@ -153,22 +155,26 @@ impl CPU {
         self.registers.r_h = 0x01;
         self.registers.r_l = 0x4D;
         self.registers.stack_pointer = 0xFFFE;
-        self.registers.program_counter = 0x0000;
+        self.registers.program_counter = memory_map::BGN;
         self.registers.address_register = 0x0000;
         self.registers.data_register = 0x00;
         self.registers.instruction_register = 0x00;
     }
 
     /// This represents the fetch–decode–execute cycle (or instruction cycle).
-    pub fn cycle(&mut self, memory: &mut Memory) -> usize {
+    pub fn cycle(&mut self, memory: &mut dyn Bus) -> usize {
         if self.stopped {
             return 1;
         }
         // Interruption Handler
         //     "Interrupts are accepted during the op code fetch cycle of each instruction."
         //     According to the "GAME BOY Programming Manual Version 1.1"
-        interrupt::handler(self, memory);
-        // Check if CPU is Halted (an interruption should reactivate the CPU instruction cycle)
+        //     The dispatch takes the place of an instruction
+        let interrupt_cycles: usize = interrupt::handler(self, memory);
+        if interrupt_cycles > 0 {
+            return interrupt_cycles;
+        }
+        // Check if CPU is Halted (a pending interruption should reactivate the CPU instruction cycle)
         if self.halted {
             return 1;
         }
@@ -177,7 +183,7 @@ impl CPU {
         //     Memory Data Register (MDR) <- Loads the data from the memory
         //     Current Instruction Register (CIR) <- Copy from the MDR (Memory Data Register)
         self.registers.address_register = self.registers.program_counter;
-        self.registers.data_register = memory.fetch(self.registers.address_register);
+        self.registers.data_register = memory.cpu_fetch(self.registers.address_register);
         self.registers.instruction_register = self.registers.data_register;
         // The PC is set to point to the next instruction or operand. This is
         // necessary for jump, call, and return instructions
@@ -192,25 +198,25 @@ impl CPU {
     /// Used to fetch operands for a given instruction.
     /// It keeps the program counter in a safe state (the instruction set executor doens't have to change it,
     /// exceptionally when the opcode states that).
-    pub fn fetch_operand(&mut self, memory: &mut Memory) -> u8 {
+    pub fn fetch_operand(&mut self, memory: &mut dyn Bus) -> u8 {
         // The PC is already pointing to the operand
         self.registers.address_register = self.registers.program_counter;
-        self.registers.data_register = memory.fetch(self.registers.address_register);
+        self.registers.data_register = memory.cpu_fetch(self.registers.address_register);
         // The PC is set to point to the next instruction or operand
         self.registers.program_counter += 1;
         self.registers.data_register
     }
 
-    pub fn fetch_data(&mut self, memory: &mut Memory, address: u16) -> u8 {
+    pub fn fetch_data(&mut self, memory: &mut dyn Bus, address: u16) -> u8 {
         self.registers.address_register = address;
-        self.registers.data_register = memory.fetch(self.registers.address_register);
+        self.registers.data_register = memory.cpu_fetch(self.registers.address_register);
         self.registers.data_register
     }
 
-    pub fn write_data(&mut self, memory: &mut Memory, address: u16, word: u8) {
+    pub fn write_data(&mut self, memory: &mut dyn Bus, address: u16, word: u8) {
         self.registers.address_register = address;
         self.registers.data_register = word;
-        memory.write(address, word);
+        memory.cpu_write(address, word);
     }
 
 }
@@ -0,0 +1,378 @@
+/// GBS (GAME BOY Sound System) Player
+///
+/// A GBS file holds the sound driver and the music data ripped from a game. The code is
+/// mapped at its load address in a synthetic ROM; the player calls the INIT routine with the
+/// song number, and then the PLAY routine at the timer rate (or the VBlank rate).
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io;
+
+use super::cpu::CPU;
+use super::timer::Timer;
+use super::memory::Memory;
+use super::bus::SystemBus;
+use super::memory_map;
+use super::cartridge::{self, Cartridge};
+use super::system::CYCLES_PER_FRAME;
+
+const HEADER_SIZE       : usize = 0x70;
+const MAGIC             : &[u8] = b"GBS";
+// The player parks the CPU on an infinite loop (JR -2) at this address, below the load address,
+// and uses it as the return address of INIT and PLAY
+const IDLE_ADDRESS      : u16   = 0x0070;
+const MIN_LOAD_ADDRESS  : u16   = 0x0080;
+// INIT may take longer than a frame (e.g. when clearing the RAM); it's given up to 2 seconds
+const INIT_CYCLES_LIMIT : usize = 120 * CYCLES_PER_FRAME;
+
+const TIMER_CLOCKS: [usize; 4] = [256, 4, 16, 64]; // Machine cycles per timer increment (TAC)
+
+#[derive(Debug)]
+pub enum GbsError {
+    Io(io::Error),
+    /// The file doesn't start with "GBS"
+    InvalidMagic,
+    /// The file is smaller than the 0x70 bytes header
+    TooShort(usize),
+    /// The code must be loaded above the RST vectors and the player idle loop (0x0080-0x7FFF)
+    InvalidLoadAddress(u16),
+}
+
+impl fmt::Display for GbsError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GbsError::Io(ref error) => write!(f, "{}", error),
+            GbsError::InvalidMagic => write!(f, "Not a GBS file"),
+            GbsError::TooShort(size) => write!(f, "GBS file is too short ({} bytes)", size),
+            GbsError::InvalidLoadAddress(address) => write!(f, "Invalid GBS load address: {:#06X}", address),
+        }
+    }
+
+}
+
+impl Error for GbsError {}
+
+impl From<io::Error> for GbsError {
+
+    fn from(error: io::Error) -> GbsError {
+        GbsError::Io(error)
+    }
+
+}
+
+/// # The GBS Header
+///
+/// 00-02  "GBS"
+/// 03     Version (1)
+/// 04     Number of songs
+/// 05     First song (1-based)
+/// 06-07  Load address
+/// 08-09  INIT address
+/// 0A-0B  PLAY address
+/// 0C-0D  Stack pointer
+/// 0E     Timer modulo (TMA)
+/// 0F     Timer control (TAC); PLAY is called at the VBlank rate unless bit 2 is set
+/// 10-2F  Title
+/// 30-4F  Author
+/// 50-6F  Copyright
+pub struct GbsHeader {
+    pub version: u8,
+    pub songs: u8,
+    pub first_song: u8,
+    pub load_address: u16,
+    pub init_address: u16,
+    pub play_address: u16,
+    pub stack_pointer: u16,
+    pub timer_modulo: u8,
+    pub timer_control: u8,
+    pub title: String,
+    pub author: String,
+    pub copyright: String,
+}
+
+impl GbsHeader {
+
+    pub fn parse(data: &[u8]) -> Result<GbsHeader, GbsError> {
+        if data.len() < HEADER_SIZE {
+            return Err(GbsError::TooShort(data.len()));
+        }
+        if &data[0..3] != MAGIC {
+            return Err(GbsError::InvalidMagic);
+        }
+
+        let word = |offset: usize| (data[offset] as u16) | ((data[offset + 1] as u16) << 8);
+        let text = |offset: usize| -> String {
+            data[offset..(offset + 32)].iter()
+                .take_while(|character| **character != 0x00)
+                .map(|character| if character.is_ascii_graphic() || *character == b' ' { *character as char } else { '?' })
+                .collect::<String>()
+                .trim_end()
+                .to_owned()
+        };
+
+        let load_address: u16 = word(0x06);
+        if !(MIN_LOAD_ADDRESS..=memory_map::RB9).contains(&load_address) {
+            return Err(GbsError::InvalidLoadAddress(load_address));
+        }
+
+        Ok(GbsHeader {
+            version: data[0x03],
+            songs: data[0x04],
+            first_song: data[0x05],
+            load_address: load_address,
+            init_address: word(0x08),
+            play_address: word(0x0A),
+            stack_pointer: word(0x0C),
+            timer_modulo: data[0x0E],
+            timer_control: data[0x0F],
+            title: text(0x10),
+            author: text(0x30),
+            copyright: text(0x50),
+        })
+    }
+
+    /// Whether PLAY is called at the timer rate (instead of the VBlank rate)
+    pub fn uses_timer(&self) -> bool {
+        (self.timer_control & 0x04) > 0
+    }
+
+}
+
+/// The synthetic cartridge: the code at its load address, switchable 16kB banks at 4000-7FFF
+/// (selected by writes to 2000-3FFF) and 8kB of RAM at A000-BFFF
+pub struct GbsCartridge {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    rom_bank: usize,
+}
+
+impl GbsCartridge {
+
+    pub fn new(header: &GbsHeader, code: &[u8]) -> GbsCartridge {
+        let mut rom: Vec<u8> = vec![0xFF; header.load_address as usize];
+
+        // The RST vectors jump to the same offsets from the load address
+        for vector in (0x00..=0x38).step_by(8) {
+            let target: u16 = header.load_address + vector as u16;
+            rom[vector] = 0xC3; // JP a16
+            rom[vector + 1] = (target & 0xFF) as u8;
+            rom[vector + 2] = (target >> 8) as u8;
+        }
+        rom[IDLE_ADDRESS as usize] = 0x18; // JR r8
+        rom[IDLE_ADDRESS as usize + 1] = 0xFE; // -2
+
+        rom.extend_from_slice(code);
+        let padding: usize = (cartridge::ROM_BANK_SIZE - rom.len() % cartridge::ROM_BANK_SIZE) % cartridge::ROM_BANK_SIZE;
+        rom.extend(vec![0xFF; padding]);
+
+        GbsCartridge {
+            rom: rom,
+            ram: vec![0; cartridge::RAM_BANK_SIZE],
+            rom_bank: 1,
+        }
+    }
+
+}
+
+impl Cartridge for GbsCartridge {
+
+    fn read_rom(&self, address: u16) -> u8 {
+        match address {
+            memory_map::ROM0..=memory_map::ROM9 => cartridge::read_bank(&self.rom, 0, address),
+            _ => cartridge::read_bank(&self.rom, self.rom_bank, address),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, word: u8) {
+        if let 0x2000..=0x3FFF = address {
+            self.rom_bank = match word {
+                0x00 => 1,
+                bank => bank as usize,
+            };
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match cartridge::ram_offset(&self.ram, 0, address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, word: u8) -> bool {
+        match cartridge::ram_offset(&self.ram, 0, address) {
+            Some(offset) => cartridge::store(&mut self.ram, offset, word),
+            None => false,
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn load_data(&mut self, _data: &[u8]) {}
+
+}
+
+/// Plays the songs of a GBS file through the CPU, the Timer and the APU
+pub struct GbsPlayer {
+    header: GbsHeader,
+    code: Vec<u8>,
+    cpu: CPU,
+    timer: Timer,
+    memory: Memory,
+    song: u8,
+    // Machine cycles until the next PLAY call
+    play_countdown: isize,
+    sample_rate: u32,
+}
+
+impl GbsPlayer {
+
+    pub fn new(data: Vec<u8>) -> Result<GbsPlayer, GbsError> {
+        let header = GbsHeader::parse(&data)?;
+        let code: Vec<u8> = data[HEADER_SIZE..].to_vec();
+        let memory = Memory::from_cartridge(Box::new(GbsCartridge::new(&header, &code)));
+        let first_song: u8 = header.first_song.max(1) - 1;
+
+        let mut player = GbsPlayer {
+            header: header,
+            code: code,
+            cpu: CPU::new(),
+            timer: Timer::new(),
+            memory: memory,
+            song: 0,
+            play_countdown: 0,
+            sample_rate: 0,
+        };
+        player.start(first_song);
+        Ok(player)
+    }
+
+    pub fn from_file(file_path: &str) -> Result<GbsPlayer, GbsError> {
+        let mut gbs_file = File::open(file_path)?;
+        let mut gbs_buffer: Vec<u8> = Vec::new();
+        gbs_file.read_to_end(&mut gbs_buffer)?;
+        GbsPlayer::new(gbs_buffer)
+    }
+
+    pub fn header(&self) -> &GbsHeader {
+        &self.header
+    }
+
+    /// The song being played (0-based)
+    pub fn song(&self) -> u8 {
+        self.song
+    }
+
+    /// Starts playing a song (0-based; wraps around the number of songs) from a clean state
+    pub fn start(&mut self, song: u8) {
+        self.song = match self.header.songs {
+            0 => 0,
+            songs => song % songs,
+        };
+
+        self.cpu = CPU::new();
+        self.cpu.boot_expected();
+        self.cpu.interruption_enabled = false;
+        self.timer = Timer::new();
+        self.memory = Memory::from_cartridge(Box::new(GbsCartridge::new(&self.header, &self.code)));
+        self.memory.post_boot();
+        self.memory.set_audio_sample_rate(self.sample_rate);
+        self.memory.write(memory_map::IE, 0x00);
+        self.memory.write(memory_map::TMA, self.header.timer_modulo);
+        self.memory.write(memory_map::TAC, self.header.timer_control);
+
+        self.cpu.registers.stack_pointer = self.header.stack_pointer;
+        self.cpu.registers.r_a = self.song;
+        self.call(self.header.init_address);
+
+        let mut cycles: usize = 0;
+        while !self.idle() && cycles < INIT_CYCLES_LIMIT {
+            cycles += self.step();
+        }
+        self.play_countdown = self.play_period();
+    }
+
+    pub fn next(&mut self) {
+        let song = self.song.wrapping_add(1);
+        self.start(song);
+    }
+
+    pub fn previous(&mut self) {
+        let song = match self.song {
+            0 => self.header.songs.max(1) - 1,
+            song => song - 1,
+        };
+        self.start(song);
+    }
+
+    /// Runs the player for (at least) the machine cycles of a frame
+    pub fn run_frame(&mut self) -> usize {
+        let mut emulated_cycles: usize = 0;
+        while emulated_cycles < CYCLES_PER_FRAME {
+            let cycles = self.step();
+            emulated_cycles += cycles;
+
+            self.play_countdown -= cycles as isize;
+            if self.play_countdown <= 0 {
+                self.play_countdown += self.play_period();
+                // A PLAY call that didn't return yet is left running
+                if self.idle() {
+                    self.call(self.header.play_address);
+                }
+            }
+        }
+        emulated_cycles
+    }
+
+    pub fn set_audio_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.memory.set_audio_sample_rate(sample_rate);
+    }
+
+    /// Interleaved stereo samples (left, right) produced since the last call
+    pub fn audio_samples(&mut self) -> Vec<f32> {
+        self.memory.audio_samples()
+    }
+
+    /// Reads from the player memory map (e.g. the sound driver state)
+    pub fn fetch(&mut self, address: u16) -> u8 {
+        self.memory.fetch(address)
+    }
+
+    fn step(&mut self) -> usize {
+        let mut bus = SystemBus::new(&mut self.memory, &mut self.timer, None);
+        let cycles: usize = self.cpu.cycle(&mut bus);
+        bus.complete_instruction(cycles)
+    }
+
+    fn idle(&self) -> bool {
+        self.cpu.registers.program_counter == IDLE_ADDRESS
+    }
+
+    /// Calls a routine, returning to the idle loop
+    fn call(&mut self, address: u16) {
+        let stack_pointer: u16 = self.cpu.registers.stack_pointer.wrapping_sub(2);
+        self.memory.write(stack_pointer, (IDLE_ADDRESS & 0xFF) as u8);
+        self.memory.write(stack_pointer.wrapping_add(1), (IDLE_ADDRESS >> 8) as u8);
+        self.cpu.registers.stack_pointer = stack_pointer;
+        self.cpu.registers.program_counter = address;
+        self.cpu.halted = false;
+    }
+
+    /// Machine cycles between PLAY calls: the timer overflow period (with the current TMA
+    /// and TAC, which the driver may change), or a frame
+    fn play_period(&mut self) -> isize {
+        if !self.header.uses_timer() {
+            return CYCLES_PER_FRAME as isize;
+        }
+        let timer_control: u8 = self.memory.fetch(memory_map::TAC);
+        let timer_modulo: u8 = self.memory.fetch(memory_map::TMA);
+        (TIMER_CLOCKS[(timer_control & 0x03) as usize] * (256 - timer_modulo as usize)) as isize
+    }
+
+}
@@ -1,5 +1,5 @@
 use super::cpu::CPU;
-use super::memory::Memory;
+use super::bus::Bus;
 use super::bit_operations;
 use super::flags;
 use super::arithmetic;
@@ -13,7 +13,7 @@ use super::disassembler;
 ///
 /// This is a Little-endian CPU -> Memory system (LSB, MSB)
 ///
-pub fn execute(cpu: &mut CPU, memory: &mut Memory, opcode: u8) -> usize {
+pub fn execute(cpu: &mut CPU, memory: &mut dyn Bus, opcode: u8) -> usize {
     match opcode {
         /* NOP  */ 0x00 => {
             debug_system!("NOP\n", cpu.debug_mode);
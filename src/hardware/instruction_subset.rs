@@ -1,11 +1,11 @@
 use super::cpu::CPU;
-use super::memory::Memory;
+use super::bus::Bus;
 use super::flags;
 use super::bit_operations;
 
 /// This function represents the instruction subset executor within the 0xCB prefix.
 #[allow(unreachable_patterns)]
-pub fn execute(cpu: &mut CPU, memory: &mut Memory, opcode: u8) -> usize {
+pub fn execute(cpu: &mut CPU, memory: &mut dyn Bus, opcode: u8) -> usize {
     match opcode {
         /* RLC r/(HL) */ 0x00..=0x07 => {
             let mut cycles = 2;
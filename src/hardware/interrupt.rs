@@ -1,10 +1,13 @@
 /// Interruption handler
 
 use super::cpu::CPU;
-use super::memory::Memory;
+use super::bus::Bus;
 use super::memory_map;
 use super::bit_operations;
 
+const INTERRUPT_MASK  : u8 = 0x1F; // IE and IF bits #0-#4
+const DISPATCH_CYCLES : usize = 5;
+
 pub enum Flag {
     VBLANK = 0x01,
     LCDC   = 0x02,
@@ -21,47 +24,40 @@ enum HandlerAddress {
     P10P13 = 0x0060,
 }
 
-pub fn handler(cpu: &mut CPU, memory: &mut Memory) {
-    let any_flags = (Flag::VBLANK as u8 | Flag::LCDC as u8 | Flag::TIMER as u8 | Flag::SERIAL as u8 | Flag::P10P13 as u8) as u8;
-    let interrupt_enable: u8 = cpu.fetch_data(memory, memory_map::IE);
-    let interrupt_request: u8 = cpu.fetch_data(memory, memory_map::IF);
+/// The interrupts both requested (IF) and enabled (IE)
+pub fn pending(memory: &mut dyn Bus) -> u8 {
+    memory.fetch(memory_map::IE) & memory.fetch(memory_map::IF) & INTERRUPT_MASK
+}
+
+/// Any pending interrupt wakes the CPU from HALT (even with IME off). With IME on, the one
+/// with the highest priority (the lowest bit) is dispatched: it's acknowledged at IF, the PC
+/// is pushed and the CPU jumps to its handler. Returns the machine cycles it took
+pub fn handler(cpu: &mut CPU, memory: &mut dyn Bus) -> usize {
+    let interrupts: u8 = pending(memory);
+    if interrupts == 0x00 {
+        return 0;
+    }
 
-    if cpu.interruption_enabled && (interrupt_enable & any_flags) > 0 {
-        // TODO check if the interrupt is enabled (IE at 0xFFFF) and if the interrupt is available (IF at 0xFF0F)
-        let handler_address: HandlerAddress = match interrupt_request {
-            interrupt_request if (Flag::VBLANK as u8 & interrupt_request) > 0 => {  // priority 0
-                let handle_request = (!(Flag::VBLANK as u8) & interrupt_request) & 0xFF;
-                cpu.write_data(memory, memory_map::IF, handle_request);
-                HandlerAddress::VBLANK
-            },
-            interrupt_request if (Flag::LCDC as u8   & interrupt_request) > 0 => {  // priority 1
-                let handle_request = (!(Flag::LCDC as u8) & interrupt_request) & 0xFF;
-                cpu.write_data(memory, memory_map::IF, handle_request);
-                HandlerAddress::LCDC
-            },
-            interrupt_request if (Flag::TIMER as u8  & interrupt_request) > 0 => {  // priority 2
-                let handle_request = (!(Flag::TIMER as u8) & interrupt_request) & 0xFF;
-                cpu.write_data(memory, memory_map::IF, handle_request);
-                HandlerAddress::TIMER
-            },
-            interrupt_request if (Flag::SERIAL as u8 & interrupt_request) > 0 => {  // priority 3
-                let handle_request = (!(Flag::SERIAL as u8) & interrupt_request) & 0xFF;
-                cpu.write_data(memory, memory_map::IF, handle_request);
-                HandlerAddress::SERIAL
-            },
-            interrupt_request if (Flag::P10P13 as u8 & interrupt_request) > 0 => {  // priority 4
-                let handle_request = (!(Flag::P10P13 as u8) & interrupt_request) & 0xFF;
-                cpu.write_data(memory, memory_map::IF, handle_request);
-                HandlerAddress::P10P13
-            },
-            _ => panic!("Could not infer interruption flag type"),
-        };
-        cpu.registers.stack_pointer = cpu.registers.stack_pointer.wrapping_sub(1);
-        cpu.write_data(memory, cpu.registers.stack_pointer, bit_operations::msb(cpu.registers.program_counter, 8));
-        cpu.registers.stack_pointer = cpu.registers.stack_pointer.wrapping_sub(1);
-        cpu.write_data(memory, cpu.registers.stack_pointer, bit_operations::lsb(cpu.registers.program_counter, 8));
-        cpu.registers.program_counter = handler_address as u16;
-        cpu.halted = false;
-        cpu.interruption_enabled = false;
+    cpu.halted = false;
+    if !cpu.interruption_enabled {
+        return 0;
     }
+
+    let (flag, handler_address): (Flag, HandlerAddress) = match interrupts {
+        interrupts if (Flag::VBLANK as u8 & interrupts) > 0 => (Flag::VBLANK, HandlerAddress::VBLANK), // priority 0
+        interrupts if (Flag::LCDC as u8   & interrupts) > 0 => (Flag::LCDC,   HandlerAddress::LCDC),   // priority 1
+        interrupts if (Flag::TIMER as u8  & interrupts) > 0 => (Flag::TIMER,  HandlerAddress::TIMER),  // priority 2
+        interrupts if (Flag::SERIAL as u8 & interrupts) > 0 => (Flag::SERIAL, HandlerAddress::SERIAL), // priority 3
+        _                                                   => (Flag::P10P13, HandlerAddress::P10P13), // priority 4
+    };
+    let interrupt_request: u8 = memory.fetch(memory_map::IF) & !(flag as u8);
+    memory.write(memory_map::IF, interrupt_request);
+    cpu.interruption_enabled = false;
+
+    cpu.registers.stack_pointer = cpu.registers.stack_pointer.wrapping_sub(1);
+    cpu.write_data(memory, cpu.registers.stack_pointer, bit_operations::msb(cpu.registers.program_counter, 8));
+    cpu.registers.stack_pointer = cpu.registers.stack_pointer.wrapping_sub(1);
+    cpu.write_data(memory, cpu.registers.stack_pointer, bit_operations::lsb(cpu.registers.program_counter, 8));
+    cpu.registers.program_counter = handler_address as u16;
+    DISPATCH_CYCLES
 }
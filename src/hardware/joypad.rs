@@ -0,0 +1,115 @@
+/// The Joypad (P1) Component
+
+/// # The P1 Register
+///
+/// 7   6   5   4   3   2   1   0
+/// 1   1   P15 P14 P13 P12 P11 P10
+///
+/// P14 and P15 are the select lines (written by the CPU; 0 selects the group):
+/// P14 selects the direction keys and P15 selects the action buttons.
+/// P10-P13 are the read lines (0 means pressed), shared by both groups:
+///
+/// P10     Right (P14) or A (P15)
+/// P11     Left  (P14) or B (P15)
+/// P12     Up    (P14) or Select (P15)
+/// P13     Down  (P14) or Start (P15)
+///
+/// The upper 2 bits are not used and always read as 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+const SELECT_DIRECTIONS : u8 = 0x10; // P14
+const SELECT_ACTIONS    : u8 = 0x20; // P15
+const SELECT_LINES      : u8 = SELECT_DIRECTIONS | SELECT_ACTIONS;
+const READ_LINES        : u8 = 0x0F; // P10-P13
+const UNUSED_BITS       : u8 = 0xC0;
+
+pub struct Joypad {
+    select: u8,
+    // Lower nibble: directions; upper nibble: actions (1 means pressed)
+    pressed: u8,
+}
+
+impl Default for Joypad {
+
+    fn default() -> Joypad {
+        Joypad::new()
+    }
+
+}
+
+impl Joypad {
+
+    pub fn new() -> Joypad {
+        Joypad {
+            select: SELECT_LINES,
+            pressed: 0x00,
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        UNUSED_BITS | self.select | self.read_lines()
+    }
+
+    /// Only the select lines are writable. Returns true when any read line goes from
+    /// high to low, which must request the P10-P13 interrupt.
+    pub fn write(&mut self, word: u8) -> bool {
+        let previous_lines: u8 = self.read_lines();
+        self.select = word & SELECT_LINES;
+        falling_edge(previous_lines, self.read_lines())
+    }
+
+    /// Returns true when any read line goes from high to low, which must request the
+    /// P10-P13 interrupt.
+    pub fn press(&mut self, button: Button) -> bool {
+        let previous_lines: u8 = self.read_lines();
+        self.pressed |= button_mask(button);
+        falling_edge(previous_lines, self.read_lines())
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.pressed &= !button_mask(button);
+    }
+
+    pub fn is_pressed(&self, button: Button) -> bool {
+        (self.pressed & button_mask(button)) != 0x00
+    }
+
+    fn read_lines(&self) -> u8 {
+        let mut lines: u8 = READ_LINES;
+        if (self.select & SELECT_DIRECTIONS) == 0x00 {
+            lines &= !(self.pressed & 0x0F);
+        }
+        if (self.select & SELECT_ACTIONS) == 0x00 {
+            lines &= !(self.pressed >> 4);
+        }
+        lines
+    }
+
+}
+
+fn button_mask(button: Button) -> u8 {
+    match button {
+        Button::Right  => 0x01,
+        Button::Left   => 0x02,
+        Button::Up     => 0x04,
+        Button::Down   => 0x08,
+        Button::A      => 0x10,
+        Button::B      => 0x20,
+        Button::Select => 0x40,
+        Button::Start  => 0x80,
+    }
+}
+
+fn falling_edge(previous_lines: u8, lines: u8) -> bool {
+    (previous_lines & !lines) != 0x00
+}
@@ -0,0 +1,145 @@
+/// MBC1 (Memory Bank Controller 1)
+
+use super::memory_map;
+use super::cartridge::{self, Cartridge, ROM_BANK_SIZE};
+
+/// # The MBC1 Registers
+///
+/// 0000-1FFF  RAM Enable (0x0A in the lower nibble enables it)
+/// 2000-3FFF  BANK1: lower 5 bits of the ROM bank number (0x00 is read as 0x01)
+/// 4000-5FFF  BANK2: 2 bits, either the upper ROM bank bits or the RAM bank number
+/// 6000-7FFF  Banking Mode Select (0: simple; 1: advanced)
+///
+/// Since BANK1 never holds 0x00, banks 0x20, 0x40 and 0x60 are not reachable through
+/// the switchable area (4000-7FFF); they are read as 0x21, 0x41 and 0x61 instead. In
+/// advanced mode, BANK2 also applies to the 0000-3FFF area and to the external RAM.
+///
+/// MBC1M (multicart) boards wire BANK1 with 4 bits only, so BANK2 is shifted by 4.
+pub struct MBC1 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    bank1: u8,
+    bank2: u8,
+    advanced_mode: bool,
+    multicart: bool,
+}
+
+impl MBC1 {
+
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> MBC1 {
+        let multicart: bool = is_multicart(&rom);
+
+        MBC1 {
+            rom: rom,
+            ram: vec![0; ram_size],
+            ram_enabled: false,
+            bank1: 0x01,
+            bank2: 0x00,
+            advanced_mode: false,
+            multicart: multicart,
+        }
+    }
+
+    /// ROM bank mapped at 0000-3FFF
+    pub fn lower_rom_bank(&self) -> usize {
+        match self.advanced_mode {
+            true  => (self.bank2 as usize) << self.bank2_shift(),
+            false => 0,
+        }
+    }
+
+    /// ROM bank mapped at 4000-7FFF
+    pub fn upper_rom_bank(&self) -> usize {
+        let bank1: u8 = match self.multicart {
+            true  => self.bank1 & 0x0F,
+            false => self.bank1,
+        };
+        ((self.bank2 as usize) << self.bank2_shift()) | bank1 as usize
+    }
+
+    /// RAM bank mapped at A000-BFFF
+    pub fn ram_bank(&self) -> usize {
+        match self.advanced_mode {
+            true  => self.bank2 as usize,
+            false => 0,
+        }
+    }
+
+    fn bank2_shift(&self) -> usize {
+        match self.multicart {
+            true  => 4,
+            false => 5,
+        }
+    }
+
+    fn ram_offset(&self, address: u16) -> Option<usize> {
+        match self.ram_enabled {
+            true  => cartridge::ram_offset(&self.ram, self.ram_bank(), address),
+            false => None,
+        }
+    }
+
+}
+
+impl Cartridge for MBC1 {
+
+    fn read_rom(&self, address: u16) -> u8 {
+        match address {
+            memory_map::ROM0..=memory_map::ROM9 => cartridge::read_bank(&self.rom, self.lower_rom_bank(), address),
+            _ => cartridge::read_bank(&self.rom, self.upper_rom_bank(), address),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, word: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = (word & 0x0F) == 0x0A,
+            0x2000..=0x3FFF => {
+                self.bank1 = word & 0x1F;
+                if self.bank1 == 0x00 {
+                    self.bank1 = 0x01;
+                }
+            },
+            0x4000..=0x5FFF => self.bank2 = word & 0x03,
+            0x6000..=0x7FFF => self.advanced_mode = (word & 0x01) == 0x01,
+            _ => {},
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match self.ram_offset(address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, word: u8) -> bool {
+        match self.ram_offset(address) {
+            Some(offset) => cartridge::store(&mut self.ram, offset, word),
+            None => false,
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    fn load_data(&mut self, data: &[u8]) {
+        cartridge::load_ram(&mut self.ram, data);
+    }
+
+}
+
+/// MBC1M multicarts are 1MB cartridges holding several games, each one starting with
+/// its own header (and the Nintendo logo) at the beginning of bank 0x10, 0x20 and 0x30.
+fn is_multicart(rom: &[u8]) -> bool {
+    let logo_start: usize = memory_map::NTD0 as usize;
+    let logo_end: usize = memory_map::NTD9 as usize + 1;
+    let second_game: usize = 0x10 * ROM_BANK_SIZE;
+
+    if rom.len() != 64 * ROM_BANK_SIZE {
+        return false;
+    }
+
+    rom[logo_start..logo_end] == rom[(second_game + logo_start)..(second_game + logo_end)]
+}
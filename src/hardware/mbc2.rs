@@ -0,0 +1,87 @@
+/// MBC2 (Memory Bank Controller 2)
+
+use super::memory_map;
+use super::cartridge::{self, Cartridge};
+
+const RAM_SIZE: usize = 0x0200; // 512 x 4 bits
+
+/// # The MBC2 Registers
+///
+/// 0000-3FFF  RAM Enable (address bit 8 clear; 0x0A in the lower nibble enables it)
+///            ROM Bank Number (address bit 8 set; 4 bits, 0x00 is read as 0x01)
+///
+/// MBC2 has a built-in RAM of 512 x 4 bits, mapped at A000-A1FF and echoed up to BFFF.
+/// Only the lower nibble of each byte is stored; the upper nibble is read as 1s.
+pub struct MBC2 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    rom_bank: u8,
+}
+
+impl MBC2 {
+
+    pub fn new(rom: Vec<u8>) -> MBC2 {
+        MBC2 {
+            rom: rom,
+            ram: vec![0; RAM_SIZE],
+            ram_enabled: false,
+            rom_bank: 0x01,
+        }
+    }
+
+    fn ram_offset(&self, address: u16) -> Option<usize> {
+        match self.ram_enabled {
+            true  => cartridge::ram_offset(&self.ram, 0, address),
+            false => None,
+        }
+    }
+
+}
+
+impl Cartridge for MBC2 {
+
+    fn read_rom(&self, address: u16) -> u8 {
+        match address {
+            memory_map::ROM0..=memory_map::ROM9 => cartridge::read_bank(&self.rom, 0, address),
+            _ => cartridge::read_bank(&self.rom, self.rom_bank as usize, address),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, word: u8) {
+        match address {
+            // The least significant bit of the upper address byte selects the register
+            0x0000..=0x3FFF if (address & 0x0100) == 0x0000 => self.ram_enabled = (word & 0x0F) == 0x0A,
+            0x0000..=0x3FFF => {
+                self.rom_bank = word & 0x0F;
+                if self.rom_bank == 0x00 {
+                    self.rom_bank = 0x01;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match self.ram_offset(address) {
+            Some(offset) => 0xF0 | self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, word: u8) -> bool {
+        match self.ram_offset(address) {
+            Some(offset) => cartridge::store(&mut self.ram, offset, word & 0x0F),
+            None => false,
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    fn load_data(&mut self, data: &[u8]) {
+        cartridge::load_ram(&mut self.ram, data);
+    }
+
+}
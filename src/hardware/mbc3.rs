@@ -0,0 +1,124 @@
+/// MBC3 (Memory Bank Controller 3)
+
+use super::memory_map;
+use super::cartridge::{self, Cartridge};
+use super::rtc::{self, RealTimeClock};
+
+/// # The MBC3 Registers
+///
+/// 0000-1FFF  RAM and Timer Enable (0x0A enables both)
+/// 2000-3FFF  ROM Bank Number (7 bits; 0x00 is read as 0x01)
+/// 4000-5FFF  RAM Bank Number (0x00-0x03) or RTC Register Select (0x08-0x0C)
+/// 6000-7FFF  Latch Clock Data (writing 0x00 and then 0x01 latches the RTC)
+///
+/// The selected RTC register is accessed through the A000-BFFF area, in place of
+/// the external RAM.
+pub struct MBC3 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    rom_bank: u8,
+    ram_select: u8,
+    rtc: Option<RealTimeClock>,
+}
+
+impl MBC3 {
+
+    pub fn new(rom: Vec<u8>, ram_size: usize, rtc: Option<RealTimeClock>) -> MBC3 {
+        MBC3 {
+            rom: rom,
+            ram: vec![0; ram_size],
+            ram_enabled: false,
+            rom_bank: 0x01,
+            ram_select: 0x00,
+            rtc: rtc,
+        }
+    }
+
+    /// Whether an RTC register (instead of a RAM bank) is mapped at A000-BFFF
+    pub fn clock_selected(&self) -> bool {
+        self.rtc.is_some() && self.ram_select >= 0x08 && self.ram_select <= 0x0C
+    }
+
+    fn ram_offset(&self, address: u16) -> Option<usize> {
+        match self.ram_enabled && self.ram_select <= 0x03 {
+            true  => cartridge::ram_offset(&self.ram, self.ram_select as usize, address),
+            false => None,
+        }
+    }
+
+}
+
+impl Cartridge for MBC3 {
+
+    fn read_rom(&self, address: u16) -> u8 {
+        match address {
+            memory_map::ROM0..=memory_map::ROM9 => cartridge::read_bank(&self.rom, 0, address),
+            _ => cartridge::read_bank(&self.rom, self.rom_bank as usize, address),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, word: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = (word & 0x0F) == 0x0A,
+            0x2000..=0x3FFF => {
+                self.rom_bank = word & 0x7F;
+                if self.rom_bank == 0x00 {
+                    self.rom_bank = 0x01;
+                }
+            },
+            0x4000..=0x5FFF => self.ram_select = word,
+            0x6000..=0x7FFF => {
+                if let Some(ref mut rtc) = self.rtc {
+                    rtc.write_latch(word);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if self.clock_selected() {
+            return match self.rtc {
+                Some(ref rtc) if self.ram_enabled => rtc.read(self.ram_select),
+                _ => 0xFF,
+            };
+        }
+        match self.ram_offset(address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, word: u8) -> bool {
+        if self.clock_selected() {
+            return match self.rtc {
+                Some(ref mut rtc) if self.ram_enabled => rtc.write(self.ram_select, word),
+                _ => false,
+            };
+        }
+        match self.ram_offset(address) {
+            Some(offset) => cartridge::store(&mut self.ram, offset, word),
+            None => false,
+        }
+    }
+
+    /// The External RAM, followed by the RTC block (if the cartridge has a timer)
+    fn save_data(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = self.ram.clone();
+        if let Some(ref rtc) = self.rtc {
+            data.extend(rtc.save_state());
+        }
+        data
+    }
+
+    fn load_data(&mut self, data: &[u8]) {
+        cartridge::load_ram(&mut self.ram, data);
+        if let Some(ref mut rtc) = self.rtc {
+            if data.len() >= self.ram.len() + rtc::SAVE_STATE_SIZE_LEGACY {
+                rtc.load_state(&data[self.ram.len()..]);
+            }
+        }
+    }
+
+}
@@ -0,0 +1,100 @@
+/// MBC5 (Memory Bank Controller 5)
+
+use super::memory_map;
+use super::cartridge::{self, Cartridge};
+
+/// # The MBC5 Registers
+///
+/// 0000-1FFF  RAM Enable (0x0A in the lower nibble enables it)
+/// 2000-2FFF  Lower 8 bits of the ROM Bank Number (bank 0x00 is valid at 4000-7FFF)
+/// 3000-3FFF  9th bit of the ROM Bank Number
+/// 4000-5FFF  RAM Bank Number (0x00-0x0F)
+///
+/// Rumble cartridges wire bit 3 of the RAM Bank Number to the motor, so only
+/// 8 RAM banks are addressable on them.
+pub struct MBC5 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    rom_bank: u16,
+    ram_bank: u8,
+    has_rumble: bool,
+    rumble_active: bool,
+}
+
+impl MBC5 {
+
+    pub fn new(rom: Vec<u8>, ram_size: usize, has_rumble: bool) -> MBC5 {
+        MBC5 {
+            rom: rom,
+            ram: vec![0; ram_size],
+            ram_enabled: false,
+            rom_bank: 0x0001,
+            ram_bank: 0x00,
+            has_rumble: has_rumble,
+            rumble_active: false,
+        }
+    }
+
+    fn ram_offset(&self, address: u16) -> Option<usize> {
+        match self.ram_enabled {
+            true  => cartridge::ram_offset(&self.ram, self.ram_bank as usize, address),
+            false => None,
+        }
+    }
+
+}
+
+impl Cartridge for MBC5 {
+
+    fn read_rom(&self, address: u16) -> u8 {
+        match address {
+            memory_map::ROM0..=memory_map::ROM9 => cartridge::read_bank(&self.rom, 0, address),
+            _ => cartridge::read_bank(&self.rom, self.rom_bank as usize, address),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, word: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = (word & 0x0F) == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x0100) | word as u16,
+            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0x00FF) | (((word & 0x01) as u16) << 8),
+            0x4000..=0x5FFF => {
+                if self.has_rumble {
+                    self.rumble_active = (word & 0x08) == 0x08;
+                    self.ram_bank = word & 0x07;
+                } else {
+                    self.ram_bank = word & 0x0F;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match self.ram_offset(address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, word: u8) -> bool {
+        match self.ram_offset(address) {
+            Some(offset) => cartridge::store(&mut self.ram, offset, word),
+            None => false,
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    fn load_data(&mut self, data: &[u8]) {
+        cartridge::load_ram(&mut self.ram, data);
+    }
+
+    fn rumble_active(&self) -> bool {
+        self.rumble_active
+    }
+
+}
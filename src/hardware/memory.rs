@@ -1,39 +1,101 @@
 /// Memory Bus
 
 use std::io::prelude::*;
+use std::io;
 use std::fs::File;
+use std::path::{Path, PathBuf};
 
 use super::memory_map;
+use super::video_mode;
 use super::cartridge_types;
+use super::cartridge::{self, Cartridge};
+use super::cartridge_header::CartridgeHeader;
+use super::boot_rom::BootRom;
+use super::joypad::{Joypad, Button};
+use super::apu::APU;
+use super::interrupt::{Flag as InterruptFlag};
+
+/// I/O registers state after the DMG BOOT ROM hands over control to the cartridge (the
+/// sound registers are set by the APU)
+const POST_BOOT_REGISTERS: [(u16, u8); 21] = [
+    (memory_map::P1,   0xCF),
+    (memory_map::SB,   0x00),
+    (memory_map::SC,   0x7E),
+    (memory_map::DIV,  0xAB),
+    (memory_map::TIMA, 0x00),
+    (memory_map::TMA,  0x00),
+    (memory_map::TAC,  0xF8),
+    (memory_map::IF,   0xE1),
+    (memory_map::LCDC, 0x91),
+    (memory_map::STAT, 0x85),
+    (memory_map::SCY,  0x00),
+    (memory_map::SCX,  0x00),
+    (memory_map::LY,   0x00),
+    (memory_map::LYC,  0x00),
+    (memory_map::DMA,  0xFF),
+    (memory_map::BGP,  0xFC),
+    (memory_map::OBP0, 0xFF),
+    (memory_map::OBP1, 0xFF),
+    (memory_map::WY,   0x00),
+    (memory_map::WX,   0x00),
+    (memory_map::DMGS, 0x01),
+];
+
+const DMA_LENGTH      : u16 = 0xA0; // Bytes copied to OAM (one per M-cycle)
+const DMA_START_DELAY : usize = 1;  // M-cycles between the write to DMA and the first copy
+
+/// An OAM DMA transfer from XX00-XX9F
+struct Dma {
+    source: u16,
+    // Bytes copied so far
+    index: u16,
+    // M-cycles before it starts
+    delay: usize,
+}
 
 pub struct ROM {
-    boot_rom_data: Vec<u8>,
-    data: Vec<u8>,
+    boot_rom: Option<BootRom>,
+    cartridge: Box<dyn Cartridge>,
+    header: Option<CartridgeHeader>,
     rom_cartridge_type: u8,
     boot_rom_enabled: bool,
 }
 
 pub struct RAM {
     data: Vec<u8>,
+    // Path to the .sav file for battery-backed cartridges
+    save_path: Option<PathBuf>,
+    // The battery-backed data has changed since the last flush
+    save_dirty: bool,
 }
 
 pub struct Memory {
     rom: ROM,
     ram: RAM,
+    joypad: Joypad,
+    apu: APU,
+    // The CPU can't access VRAM and OAM while the PPU is using them
+    access_restricted: bool,
+    // The running OAM DMA transfer, and the one requested by writing to DMA (which takes
+    // over once it starts)
+    dma: Option<Dma>,
+    dma_request: Option<Dma>,
 }
 
 impl Memory {
 
-    pub fn new(file_path: String) -> Memory {
-        let mut boot_rom_file = File::open("./data/DMG_ROM.bin").expect("Could not find BOOT ROM file; aborting");
-        let mut boot_rom_buffer: Vec<u8> = Vec::new();
+    /// Without a BOOT ROM, nothing is mapped at the Internal ROM area and the `post_boot`
+    /// state should be applied instead
+    pub fn new(file_path: String, mut boot_rom: Option<BootRom>) -> Memory {
         let mut rom_file;
         let mut rom_buffer: Vec<u8>;
         let ram_buffer: Vec<u8>;
         let rom_cartridge_type: u8;
+        let header: Option<CartridgeHeader>;
         let memory_size: usize;
-        let rom: ROM;
+        let mut rom: ROM;
         let ram: RAM;
+        let mut save_path: Option<PathBuf> = None;
 
         let debug_mode: bool = debug_mode!();
 
@@ -42,20 +104,35 @@ impl Memory {
             rom_buffer = Vec::new();
             rom_file.read_to_end(&mut rom_buffer).expect("Could not load ROM file; aborting");
             rom_cartridge_type = rom_buffer[memory_map::RTC as usize];
+            if cartridge_types::has_battery(rom_cartridge_type) {
+                save_path = Some(Path::new(&file_path).with_extension("sav"));
+            }
         } else {
             rom_buffer = vec![0; 16_384]; // 16kB of Empty ROM
             rom_cartridge_type = cartridge_types::ROM_ONLY;
         }
 
-        boot_rom_file.read_to_end(&mut boot_rom_buffer).expect("Could not load BOOT ROM file; aborting");
         debug_system!(format!("Cartridge type: {:#04X}\n", rom_cartridge_type), debug_mode);
 
+        // The real hardware doesn't check the ROM size nor the checksums (only the BOOT ROM
+        // checks the logo and the header complement), so mismatches are only reported
+        header = CartridgeHeader::parse(&rom_buffer).ok();
+        if let Some(ref header) = header {
+            debug_system!(format!("Cartridge title: {}\n", header.title), debug_mode);
+            if header.rom_size() != rom_buffer.len() {
+                debug_system!(format!("ROM Size Mismatch: {} & {}\n", header.rom_size(), rom_buffer.len()), debug_mode);
+            }
+            if !header.is_valid() {
+                debug_system!("Cartridge header is not valid (logo or complement check)\n", debug_mode);
+            }
+        }
+
         let until: u16 = debug_until!();
         if until != 0xFFFF {
-            if until < memory_map::IROX {
-                boot_rom_buffer[until as usize] = 0x00; // NOP
-                boot_rom_buffer[(until + 0x0002) as usize] = 0x00; // STOP 00
-                boot_rom_buffer[(until + 0x0001) as usize] = 0x10; //
+            if let (true, Some(ref mut boot_rom)) = (until < memory_map::IROX, boot_rom.as_mut()) {
+                boot_rom.patch(until, 0x00); // NOP
+                boot_rom.patch(until + 0x0002, 0x00); // STOP 00
+                boot_rom.patch(until + 0x0001, 0x10); //
             } else {
                 rom_buffer[until as usize] = 0x00; // NOP
                 rom_buffer[(until + 0x0002) as usize] = 0x00; // STOP 00
@@ -63,30 +140,106 @@ impl Memory {
             }
         }
 
-        // TODO Should I check the ROM size? I don't think the real hardware does that
-        // if rom_buffer[memory_map::OSIZ as usize] as usize != rom_buffer.len() {
-        //     panic!("ROM Size Mismatch: ${:02X} & ${:02X}",
-        //         rom_buffer[memory_map::OSIZ as usize],
-        //         rom_buffer.len());
-        // }
         memory_size = (0xFFFF as usize) + 1;
         ram_buffer = vec![0; memory_size];
         debug_system!(format!("RAM Size: {}\n", ram_buffer.len()), debug_mode);
 
         rom = ROM {
-            data: rom_buffer,
-            boot_rom_data: boot_rom_buffer,
+            boot_rom_enabled: boot_rom.is_some(),
+            boot_rom: boot_rom,
+            cartridge: cartridge::from_rom(rom_buffer),
+            header: header,
             rom_cartridge_type: rom_cartridge_type,
-            boot_rom_enabled: true,
         };
 
+        if let Some(ref path) = save_path {
+            if path.exists() {
+                let mut save_file = File::open(path).expect("Could not open save file; aborting");
+                let mut save_buffer: Vec<u8> = Vec::new();
+                save_file.read_to_end(&mut save_buffer).expect("Could not load save file; aborting");
+                rom.cartridge.load_data(&save_buffer);
+                debug_system!(format!("Save file loaded: {}\n", path.display()), debug_mode);
+            }
+        }
+
         ram = RAM {
             data: ram_buffer,
+            save_path: save_path,
+            save_dirty: false,
         };
 
         Memory {
             rom: rom,
             ram: ram,
+            joypad: Joypad::new(),
+            apu: APU::new(),
+            access_restricted: true,
+            dma: None,
+            dma_request: None,
+        }
+    }
+
+    /// A Memory Bus around a synthetic cartridge (e.g. the GBS player), without a BOOT ROM,
+    /// cartridge header nor save file
+    pub fn from_cartridge(cartridge: Box<dyn Cartridge>) -> Memory {
+        Memory {
+            rom: ROM {
+                boot_rom: None,
+                cartridge: cartridge,
+                header: None,
+                rom_cartridge_type: cartridge_types::ROM_ONLY,
+                boot_rom_enabled: false,
+            },
+            ram: RAM {
+                data: vec![0; (0xFFFF as usize) + 1],
+                save_path: None,
+                save_dirty: false,
+            },
+            joypad: Joypad::new(),
+            apu: APU::new(),
+            access_restricted: true,
+            dma: None,
+            dma_request: None,
+        }
+    }
+
+    /// A read by the CPU: VRAM is not accessible during mode 3, nor OAM during modes 2 and 3,
+    /// nor anything but the I/O registers and HRAM during an OAM DMA transfer (reads return 0xFF)
+    pub fn cpu_fetch(&mut self, address: u16) -> u8 {
+        match self.accessible(address) {
+            true  => self.fetch(address),
+            false => 0xFF,
+        }
+    }
+
+    /// A write by the CPU: writes to VRAM or OAM while they're not accessible are ignored
+    pub fn cpu_write(&mut self, address: u16, word: u8) {
+        if self.accessible(address) {
+            self.write(address, word);
+        }
+    }
+
+    /// Lifts (or restores) the VRAM and OAM restrictions on the CPU, e.g. for debugging
+    pub fn set_access_restrictions(&mut self, enabled: bool) {
+        self.access_restricted = enabled;
+    }
+
+    /// The PPU drives the restrictions through the mode it sets at STAT; with the LCD off,
+    /// VRAM and OAM are always accessible
+    fn accessible(&self, address: u16) -> bool {
+        // The OAM DMA transfer holds the buses (the I/O registers and HRAM are internal)
+        if self.dma.is_some() && address < memory_map::HRAM {
+            return false;
+        }
+        if !self.access_restricted || (self.ram.data[memory_map::LCDC as usize] & 0x80) == 0x00 {
+            return true;
+        }
+
+        let mode: u8 = self.ram.data[memory_map::STAT as usize] & 0x03;
+        match address {
+            memory_map::VR0 ..=memory_map::VR9  => mode != video_mode::SCANLINE,
+            memory_map::OAM0..=memory_map::OAM9 => mode != video_mode::SCANLINE && mode != video_mode::SEARCH_OAM,
+            _ => true,
         }
     }
 
@@ -94,20 +247,21 @@ impl Memory {
     pub fn fetch(&mut self, address: u16) -> u8 {
         match address {
             // Internal / BOOT ROM (if enabled; external ROM otherwise)
-            memory_map::IROM..=memory_map::IROX => {
-                match self.rom.boot_rom_enabled {
-                    true => self.rom.boot_rom_data[address as usize],
-                    _ =>    self.rom.data[address as usize],
+            //     The CGB BOOT ROM is mapped at 0000-00FF and 0200-08FF
+            memory_map::IROM..=memory_map::ROM9 if self.boot_rom_maps(address) => {
+                match self.rom.boot_rom {
+                    Some(ref boot_rom) => boot_rom.read(address),
+                    None => 0xFF,
                 }
             },
             // Non-switchable ROM Bank #0 (always accessible)
-            memory_map::IROZ..=memory_map::ROM9 => self.rom.data[address as usize],
+            memory_map::IROM..=memory_map::ROM9 => self.rom.cartridge.read_rom(address),
             // Switchable / External ROM Bank
-            memory_map::RB0 ..=memory_map::RB9  => 0xFF,
+            memory_map::RB0 ..=memory_map::RB9  => self.rom.cartridge.read_rom(address),
             // VRAM
             memory_map::VR0 ..=memory_map::VR9  => self.ram.data[address as usize],
             // Switchable / External RAM Bank
-            memory_map::SWR0..=memory_map::SWR9 => 0xFF,
+            memory_map::SWR0..=memory_map::SWR9 => self.rom.cartridge.read_ram(address),
             // Internal (Work) RAM
             memory_map::WR0 ..=memory_map::WR9  => self.ram.data[address as usize],
             // ECHO of RAM
@@ -117,6 +271,14 @@ impl Memory {
             memory_map::OAM0..=memory_map::OAM9  => self.ram.data[address as usize],
             // Un-used High RAM Area
             memory_map::RAM0..=memory_map::URAM => 0xFF,
+            // Joypad
+            memory_map::P1 => self.joypad.read(),
+            // Sound registers and Wave Pattern RAM
+            memory_map::NR10..=memory_map::WPR9 => self.apu.read(address),
+            // Interrupt Flag (bits #5-#7 are unused and always read 1)
+            memory_map::IF => self.ram.data[address as usize] | 0xE0,
+            // LCD Status (bit #7 is unused and always reads 1)
+            memory_map::STAT => self.ram.data[address as usize] | 0x80,
             // Usable High RAM Area
             memory_map::HRAM..=memory_map::RAM9 => self.ram.data[address as usize],
             // This is by definition unreachable, since the address (u16) maximum value is 0xFFFF
@@ -128,15 +290,19 @@ impl Memory {
     pub fn write(&mut self, address: u16, word: u8) {
         match address {
             // Internal / BOOT ROM (if enabled; external ROM otherwise)
-            memory_map::IROM..=memory_map::IROX => {},
             // Non-switchable ROM Bank #0 (always accessible)
-            memory_map::IROZ..=memory_map::ROM9 => {},
             // Switchable / External ROM Bank
-            memory_map::RB0 ..=memory_map::RB9  => {},
+            //     Writes to the ROM area are caught by the Memory Bank Controller (if any)
+            memory_map::IROM..=memory_map::RB9  => self.rom.cartridge.write_rom(address, word),
             // VRAM
             memory_map::VR0 ..=memory_map::VR9  => self.ram.data[address as usize] = word,
             // Switchable / External RAM Bank
-            memory_map::SWR0..=memory_map::SWR9 => {},
+            //     Only changes to battery-backed RAM need to be flushed to the .sav file
+            memory_map::SWR0..=memory_map::SWR9 => {
+                if self.rom.cartridge.write_ram(address, word) && self.ram.save_path.is_some() {
+                    self.ram.save_dirty = true;
+                }
+            },
             // Internal (Work) RAM
             memory_map::WR0 ..=memory_map::WR9  => self.ram.data[address as usize] = word,
             // ECHO of RAM
@@ -147,6 +313,20 @@ impl Memory {
             memory_map::OAM0..=memory_map::OAM9  => self.ram.data[address as usize] = word,
             // Un-used High RAM Area
             memory_map::RAM0..=memory_map::URAM => {},
+            // Joypad
+            memory_map::P1 => {
+                if self.joypad.write(word) {
+                    self.request_interrupt(InterruptFlag::P10P13);
+                }
+            },
+            // Sound registers and Wave Pattern RAM
+            memory_map::NR10..=memory_map::WPR9 => self.apu.write(address, word),
+            // LCD Status: only the interrupt selection (bits #3-#6) is writable; the mode and the
+            // LYC match flag are set by the PPU (`set_lcd_status`)
+            memory_map::STAT => {
+                let status: u8 = self.ram.data[address as usize];
+                self.ram.data[address as usize] = (word & 0x78) | (status & 0x07);
+            },
             // Usable High RAM Area
             memory_map::HRAM..=memory_map::RAM9 => {
                 if address == memory_map::DMGS && word == 0x01 {
@@ -156,14 +336,16 @@ impl Memory {
                 }
 
                 if address == memory_map::DMA {
-                    // perform DMA
-                    let start_address: u16 = (word as u16) << 8;
-                    let mut i: u16 = 0;
-                    while i < 0xA0 {
-                        self.ram.data[(memory_map::OAM0 | i) as usize] = self.ram.data[(start_address + i) as usize];
-                        i += 1;
-                    }
-                    return;
+                    // Request an OAM DMA transfer; sources above DFFF read the Work RAM (on DMG)
+                    let source: u16 = match word {
+                        0xE0..=0xFF => ((word - 0x20) as u16) << 8,
+                        _ => (word as u16) << 8,
+                    };
+                    self.dma_request = Some(Dma {
+                        source: source,
+                        index: 0,
+                        delay: DMA_START_DELAY,
+                    });
                 }
 
                 self.ram.data[address as usize] = word;
@@ -173,4 +355,140 @@ impl Memory {
         }
     }
 
+    /// Sets the read-only bits of STAT (the mode and the LYC match flag), keeping the
+    /// interrupt selection
+    pub fn set_lcd_status(&mut self, mode: u8, lyc_match: bool) {
+        let status: u8 = self.ram.data[memory_map::STAT as usize] & 0x78;
+        self.ram.data[memory_map::STAT as usize] = status | (mode & 0x03) | match lyc_match {
+            true  => 0x04,
+            false => 0x00,
+        };
+    }
+
+    pub fn boot_rom_enabled(&self) -> bool {
+        self.rom.boot_rom_enabled
+    }
+
+    fn boot_rom_maps(&self, address: u16) -> bool {
+        match self.rom.boot_rom {
+            Some(ref boot_rom) => self.rom.boot_rom_enabled && boot_rom.maps(address),
+            None => false,
+        }
+    }
+
+    /// Sets the I/O registers to the values left by the DMG BOOT ROM, as if it had run
+    pub fn post_boot(&mut self) {
+        for &(address, word) in POST_BOOT_REGISTERS.iter() {
+            match address {
+                memory_map::P1 => { self.joypad.write(word); },
+                _ => self.ram.data[address as usize] = word,
+            }
+        }
+        self.apu.post_boot();
+        self.rom.boot_rom_enabled = false;
+    }
+
+    pub fn press_button(&mut self, button: Button) {
+        if self.joypad.press(button) {
+            self.request_interrupt(InterruptFlag::P10P13);
+        }
+    }
+
+    pub fn release_button(&mut self, button: Button) {
+        self.joypad.release(button);
+    }
+
+    fn request_interrupt(&mut self, flag: InterruptFlag) {
+        self.ram.data[memory_map::IF as usize] |= flag as u8;
+    }
+
+    /// Advances the components attached to the Memory Bus (the cartridge, the APU and the
+    /// OAM DMA transfer)
+    pub fn tick(&mut self, cycles: usize) {
+        self.rom.cartridge.tick(cycles);
+        self.apu.tick(cycles);
+        for _ in 0..cycles {
+            self.dma_cycle();
+        }
+    }
+
+    pub fn dma_active(&self) -> bool {
+        self.dma.is_some()
+    }
+
+    /// Copies a byte through the Memory Bus (any source, including the cartridge); a new
+    /// transfer replaces the running one once its start delay is over
+    fn dma_cycle(&mut self) {
+        if let Some(mut dma) = self.dma.take() {
+            let word: u8 = self.fetch(dma.source + dma.index);
+            self.ram.data[(memory_map::OAM0 + dma.index) as usize] = word;
+            dma.index += 1;
+            if dma.index < DMA_LENGTH {
+                self.dma = Some(dma);
+            }
+        }
+
+        if let Some(mut dma_request) = self.dma_request.take() {
+            dma_request.delay -= 1;
+            match dma_request.delay {
+                0 => self.dma = Some(dma_request),
+                _ => self.dma_request = Some(dma_request),
+            }
+        }
+    }
+
+    /// The current APU output (left and right terminals)
+    pub fn audio_output(&self) -> (f32, f32) {
+        self.apu.output()
+    }
+
+    pub fn set_audio_sample_rate(&mut self, sample_rate: u32) {
+        self.apu.set_sample_rate(sample_rate);
+    }
+
+    pub fn audio_sample_rate(&self) -> u32 {
+        self.apu.sample_rate()
+    }
+
+    /// The APU samples (interleaved left and right) produced since the last call
+    pub fn audio_samples(&mut self) -> Vec<f32> {
+        self.apu.take_samples()
+    }
+
+    /// The cartridge header, if the ROM is large enough to hold one
+    pub fn cartridge_header(&self) -> Option<&CartridgeHeader> {
+        self.rom.header.as_ref()
+    }
+
+    /// The battery-backed data, in the raw .sav layout
+    pub fn export_save_ram(&self) -> Vec<u8> {
+        self.rom.cartridge.save_data()
+    }
+
+    pub fn import_save_ram(&mut self, data: &[u8]) {
+        self.rom.cartridge.load_data(data);
+        self.ram.save_dirty = true;
+    }
+
+    /// Whether the battery-backed data has changed since the last flush
+    pub fn save_ram_dirty(&self) -> bool {
+        self.ram.save_dirty
+    }
+
+    /// Writes the battery-backed data to the .sav file next to the ROM (if the
+    /// cartridge has a battery)
+    pub fn flush_save_ram(&mut self) -> io::Result<()> {
+        if let Some(ref path) = self.ram.save_path {
+            let mut save_file = File::create(path)?;
+            save_file.write_all(&self.rom.cartridge.save_data())?;
+        }
+        self.ram.save_dirty = false;
+        Ok(())
+    }
+
+    /// Whether the cartridge is requesting its rumble motor to be active
+    pub fn rumble_active(&self) -> bool {
+        self.rom.cartridge.rumble_active()
+    }
+
 }
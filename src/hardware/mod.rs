@@ -2,8 +2,17 @@
 mod debug_macros;
 
 pub mod cartridge_types;
+pub mod rtc;
+pub mod cartridge;
+pub mod cartridge_header;
+pub mod boot_rom;
+pub mod mbc1;
+pub mod mbc2;
+pub mod mbc3;
+pub mod mbc5;
 pub mod memory_map;
 pub mod memory;
+pub mod bus;
 pub mod flags;
 pub mod arithmetic;
 pub mod bit_operations;
@@ -11,9 +20,14 @@ pub mod instruction_subset;
 pub mod instruction_set;
 pub mod interrupt;
 pub mod timer;
+pub mod joypad;
+pub mod apu;
+pub mod wav;
+pub mod gbs;
 pub mod cpu;
 pub mod ppu;
 pub mod system;
 pub mod disassembler;
 pub mod video;
+pub mod pixel_fifo;
 pub mod video_mode;
@@ -0,0 +1,350 @@
+/// The Pixel FIFO Renderer
+///
+/// Draws a line one pixel (dot) at a time during mode 3: the fetcher reads the BG/window
+/// tiles into the BG FIFO, objects are fetched into the OBJ FIFO as the line reaches them
+/// (stalling the output), and each dot shifts a pixel out to the LCD. Registers are read
+/// as the line is drawn, so changes to SCX, BGP, LCDC etc. in the middle of a line show up,
+/// and the length of mode 3 depends on the SCX fine scroll, the window and the objects.
+///
+/// The fetcher is stalled along with the output, rather than finishing its step while the
+/// object is fetched; instead, each object adds the time it'd wait for the fetcher: 6 dots,
+/// plus up to 5 for the first object on a BG/window tile (depending on where in the tile
+/// it starts). Objects at X=0 are hidden, and take no time.
+
+use std::collections::VecDeque;
+
+use super::memory::Memory;
+use super::memory_map;
+use super::bit_operations;
+use super::video::{self, Video, Object, FRAME_WIDTH};
+
+const STARTUP_DOTS      : usize = 6; // The first (discarded) tile fetch of the line
+const FETCH_STEP_DOTS   : usize = 2;
+const OBJECT_FETCH_DOTS : usize = 6;
+const TILE_DIMENSION    : u8 = 8;
+const WINDOW_X_OFFSET   : u8 = 7;
+const WINDOW_X_MAX      : u8 = 166;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FetcherStep {
+    Tile,
+    DataLow,
+    DataHigh,
+    Push,
+}
+
+#[derive(Clone, Copy)]
+struct ObjectPixel {
+    color_number: u8,
+    obp1: bool,
+    behind_background: bool,
+}
+
+const TRANSPARENT: ObjectPixel = ObjectPixel {
+    color_number: 0,
+    obp1: false,
+    behind_background: false,
+};
+
+pub struct PixelFifo {
+    ly: u8,
+    // Pixels shifted out to the LCD
+    x: usize,
+    // Dots spent on the line (the length of mode 3)
+    dots: usize,
+    // Pixels to drop from the BG FIFO (the SCX fine scroll, or the window with WX<7)
+    discard: u8,
+    // Dots in which the output is stalled (line start and object fetches)
+    stall: usize,
+    background_fifo: VecDeque<u8>,
+    object_fifo: VecDeque<ObjectPixel>,
+    fetcher_step: FetcherStep,
+    fetcher_dots: usize,
+    fetcher_x: u8,
+    character_code: u8,
+    lsb: u8,
+    msb: u8,
+    // The fetcher is reading the window tiles
+    window: bool,
+    // The window of the previous line reached the end of it, so it's drawn from the start
+    window_continues: bool,
+    // The last BG/window tile an object waited for the fetcher on (window, tile column)
+    penalized_tile: Option<(bool, i16)>,
+    // The objects found by the OAM search, not fetched yet (by X position, then OAM order)
+    objects: VecDeque<Object>,
+    done: bool,
+}
+
+impl PixelFifo {
+
+    pub fn new() -> PixelFifo {
+        PixelFifo {
+            ly: 0,
+            x: 0,
+            dots: 0,
+            discard: 0,
+            stall: 0,
+            background_fifo: VecDeque::with_capacity(16),
+            object_fifo: VecDeque::with_capacity(8),
+            fetcher_step: FetcherStep::Tile,
+            fetcher_dots: 0,
+            fetcher_x: 0,
+            character_code: 0,
+            lsb: 0,
+            msb: 0,
+            window: false,
+            window_continues: false,
+            penalized_tile: None,
+            objects: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Starts mode 3, after the OAM search
+    pub fn start_line(&mut self, memory: &mut Memory, video: &mut Video) {
+        self.ly = memory.fetch(memory_map::LY);
+        video.start_line(self.ly, memory.fetch(memory_map::WY));
+
+        let mut objects: Vec<Object> = video.line_objects.clone();
+        objects.sort_by_key(|object| (object.x, object.index));
+
+        self.x = 0;
+        self.dots = 0;
+        self.discard = memory.fetch(memory_map::SCX) % TILE_DIMENSION;
+        self.stall = STARTUP_DOTS;
+        self.background_fifo.clear();
+        self.object_fifo.clear();
+        self.fetcher_step = FetcherStep::Tile;
+        self.fetcher_dots = 0;
+        self.fetcher_x = 0;
+        self.window = false;
+        self.window_continues = video.window_spans_line;
+        video.window_spans_line = false;
+        self.penalized_tile = None;
+        self.objects = objects.into_iter().collect();
+        self.done = false;
+    }
+
+    /// Runs the given amount of dots (T-cycles); returns true once the line is complete
+    pub fn advance(&mut self, memory: &mut Memory, video: &mut Video, dots: usize) -> bool {
+        for _ in 0..dots {
+            if self.done {
+                break;
+            }
+            self.dots += 1;
+            self.step(memory, video);
+        }
+        self.done
+    }
+
+    /// The length of mode 3 for the current line, in dots
+    pub fn mode3_dots(&self) -> usize {
+        self.dots
+    }
+
+    fn step(&mut self, memory: &mut Memory, video: &mut Video) {
+        if self.stall > 0 {
+            self.stall -= 1;
+            return;
+        }
+
+        let lcdc: u8 = memory.fetch(memory_map::LCDC);
+
+        if !self.window && self.discard == 0 {
+            self.start_window(memory, video, lcdc);
+        }
+
+        // An object is fetched when the output reaches its left edge
+        if let Some(object) = self.objects.front().cloned() {
+            if object.x as usize <= self.x + TILE_DIMENSION as usize && self.discard == 0 {
+                self.objects.pop_front();
+                // Objects at X=0 are hidden; with OBJ disabled, nothing is fetched
+                if object.x > 0 && bit_operations::simple_bit(lcdc, 1) {
+                    self.fetch_object(memory, lcdc, object);
+                    self.stall = OBJECT_FETCH_DOTS + self.fetcher_wait(memory, object) - 1;
+                    return;
+                }
+            }
+        }
+
+        self.step_fetcher(memory, video, lcdc);
+        self.shift_pixel(memory, video, lcdc);
+    }
+
+    /// Restarts the fetcher on the window once the output reaches WX-7 (the window is hidden
+    /// along with the BG on the DMG); the new tile fetch takes 6 dots
+    fn start_window(&mut self, memory: &mut Memory, video: &mut Video, lcdc: u8) {
+        if !bit_operations::simple_bit(lcdc, 0) || !bit_operations::simple_bit(lcdc, 5) || !video.window_triggered {
+            return;
+        }
+
+        let wx: u8 = memory.fetch(memory_map::WX);
+        let spans_line: bool = self.window_continues;
+        if !spans_line && (wx > WINDOW_X_MAX || self.x + (WINDOW_X_OFFSET as usize) < wx as usize) {
+            return;
+        }
+
+        self.window = true;
+        self.background_fifo.clear();
+        self.fetcher_step = FetcherStep::Tile;
+        self.fetcher_dots = 0;
+        self.fetcher_x = 0;
+        self.discard = match (spans_line, wx < WINDOW_X_OFFSET) {
+            (false, true) => WINDOW_X_OFFSET - wx,
+            _ => 0,
+        };
+        video.window_spans_line = wx == WINDOW_X_MAX;
+    }
+
+    /// The dots an object waits for the fetcher (0 to 5): only the first object on a tile
+    /// waits, the longer the closer it starts to the left edge of the tile
+    fn fetcher_wait(&mut self, memory: &mut Memory, object: Object) -> usize {
+        let x: i16 = object.x.saturating_sub(TILE_DIMENSION) as i16;
+        // The position of the object in the tiles being fetched
+        let tile_x: i16 = match (self.window, self.window_continues) {
+            (true, true)  => x,
+            (true, false) => x + WINDOW_X_OFFSET as i16 - memory.fetch(memory_map::WX) as i16,
+            (false, _)    => x + (memory.fetch(memory_map::SCX) % TILE_DIMENSION) as i16,
+        };
+        let tile: (bool, i16) = (self.window, tile_x.div_euclid(TILE_DIMENSION as i16));
+        if self.penalized_tile == Some(tile) {
+            return 0;
+        }
+        self.penalized_tile = Some(tile);
+        5usize.saturating_sub(tile_x.rem_euclid(TILE_DIMENSION as i16) as usize)
+    }
+
+    fn step_fetcher(&mut self, memory: &mut Memory, video: &mut Video, lcdc: u8) {
+        if self.fetcher_step == FetcherStep::Push {
+            if self.background_fifo.is_empty() {
+                for column in 0..TILE_DIMENSION {
+                    self.background_fifo.push_back(video::color_number(self.lsb, self.msb, 7 - column));
+                }
+                self.fetcher_x = self.fetcher_x.wrapping_add(1);
+                self.fetcher_step = FetcherStep::Tile;
+            }
+            return;
+        }
+
+        self.fetcher_dots += 1;
+        if self.fetcher_dots < FETCH_STEP_DOTS {
+            return;
+        }
+        self.fetcher_dots = 0;
+
+        // The line of the BG map (or of the window) being drawn, and the tile column
+        let (code_area_flag, map_y, map_x): (usize, u8, u8) = match self.window {
+            true  => (6, video.window_line, self.fetcher_x),
+            false => {
+                let scx: u8 = memory.fetch(memory_map::SCX);
+                let scy: u8 = memory.fetch(memory_map::SCY);
+                (3, self.ly.wrapping_add(scy), (scx / TILE_DIMENSION).wrapping_add(self.fetcher_x))
+            },
+        };
+        let tile_row: u16 = (map_y % TILE_DIMENSION) as u16;
+
+        match self.fetcher_step {
+            FetcherStep::Tile => {
+                let code_area: u16 = match bit_operations::simple_bit(lcdc, code_area_flag) {
+                    true  => 0x9C00,
+                    false => 0x9800,
+                };
+                let block_id: u16 = (map_y / TILE_DIMENSION) as u16 * 32 + (map_x % 32) as u16;
+                self.character_code = memory.fetch(code_area + block_id);
+                self.fetcher_step = FetcherStep::DataLow;
+            },
+            FetcherStep::DataLow => {
+                let offset: u16 = video::character_data_offset(lcdc, self.character_code);
+                self.lsb = memory.fetch(offset + tile_row * 2);
+                self.fetcher_step = FetcherStep::DataHigh;
+            },
+            FetcherStep::DataHigh => {
+                let offset: u16 = video::character_data_offset(lcdc, self.character_code);
+                self.msb = memory.fetch(offset + tile_row * 2 + 1);
+                self.fetcher_step = FetcherStep::Push;
+            },
+            FetcherStep::Push => {},
+        }
+    }
+
+    /// Mixes the object pixels into the OBJ FIFO; pixels of the objects already there
+    /// (smaller X, or earlier in OAM) take priority
+    fn fetch_object(&mut self, memory: &mut Memory, lcdc: u8, object: Object) {
+        let height: i16 = video::object_height(lcdc);
+        let mut row: i16 = self.ly as i16 - (object.y as i16 - 16);
+        if bit_operations::simple_bit(object.attributes, 6) {
+            row = height - 1 - row;
+        }
+        let character_code: u8 = match height {
+            16 => object.character_code & 0xFE,
+            _  => object.character_code,
+        };
+        let offset: u16 = 0x8000 + character_code as u16 * 16 + row as u16 * 2;
+        let lsb: u8 = memory.fetch(offset);
+        let msb: u8 = memory.fetch(offset + 1);
+
+        for column in 0..(TILE_DIMENSION as i16) {
+            let x: i16 = object.x as i16 - 8 + column;
+            if x < self.x as i16 {
+                continue;
+            }
+            let index: usize = (x - self.x as i16) as usize;
+            while self.object_fifo.len() <= index {
+                self.object_fifo.push_back(TRANSPARENT);
+            }
+            let pixel_x: u8 = match bit_operations::simple_bit(object.attributes, 5) {
+                true  => column as u8,
+                false => 7 - column as u8,
+            };
+            let color_number: u8 = video::color_number(lsb, msb, pixel_x);
+            if self.object_fifo[index].color_number == 0x00 && color_number != 0x00 {
+                self.object_fifo[index] = ObjectPixel {
+                    color_number: color_number,
+                    obp1: bit_operations::simple_bit(object.attributes, 4),
+                    behind_background: bit_operations::simple_bit(object.attributes, 7),
+                };
+            }
+        }
+    }
+
+    fn shift_pixel(&mut self, memory: &mut Memory, video: &mut Video, lcdc: u8) {
+        let background: u8 = match self.background_fifo.pop_front() {
+            Some(color_number) => color_number,
+            None => return,
+        };
+        if self.discard > 0 {
+            self.discard -= 1;
+            return;
+        }
+        let object: ObjectPixel = self.object_fifo.pop_front().unwrap_or(TRANSPARENT);
+
+        let background: u8 = match bit_operations::simple_bit(lcdc, 0) {
+            true  => background,
+            false => 0x00,
+        };
+        let object_visible: bool = object.color_number != 0x00
+            && bit_operations::simple_bit(lcdc, 1)
+            && !(object.behind_background && background != 0x00);
+        let color: u8 = match object_visible {
+            true => {
+                let palette: u8 = match object.obp1 {
+                    true  => memory.fetch(memory_map::OBP1),
+                    false => memory.fetch(memory_map::OBP0),
+                };
+                (palette >> (object.color_number * 2)) & 0x03
+            },
+            false => (memory.fetch(memory_map::BGP) >> (background * 2)) & 0x03,
+        };
+
+        video.frame_buffer[self.ly as usize * FRAME_WIDTH + self.x] = color;
+        self.x += 1;
+
+        if self.x == FRAME_WIDTH {
+            if self.window {
+                video.window_line = video.window_line.wrapping_add(1);
+            }
+            self.done = true;
+        }
+    }
+
+}
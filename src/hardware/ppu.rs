@@ -3,6 +3,7 @@
 use super::memory::Memory;
 use super::memory_map;
 use super::video::{Video, FRAME_HEIGHT, FRAME_HEIGHT_FULL};
+use super::pixel_fifo::PixelFifo;
 use super::video_mode;
 use super::interrupt::{Flag as InterruptFlag};
 use super::bit_operations;
@@ -12,6 +13,8 @@ const MODE01_THRESHOLD: usize = 1140; // 4560 div 4 -> VBLANK threshold
 const MODE10_THRESHOLD: usize = 20;   // 80 div 4   -> SEARCH_OAM threshold
 const MODE11_THRESHOLD: usize = 43;   // 172 div 4  -> SCANLINE threshold
 
+const T_CYCLES_PER_M_CYCLE: usize = 4;
+
 const VBLANK_LINES:           usize = 10;
 const VBLANK_CYCLES_PER_LINE: usize = MODE01_THRESHOLD / VBLANK_LINES;
 
@@ -22,12 +25,31 @@ pub struct PPURegisters {
     data_register: u8,
 }
 
+/// The scanline renderer draws each line at once, at the end of a fixed-length mode 3. The
+/// pixel FIFO renderer draws it along mode 3 (whose length varies), so mid-line changes
+/// to the registers show up, at a higher cost
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Renderer {
+    Scanline,
+    PixelFifo,
+}
+
 pub struct PPU {
     pub registers: PPURegisters,
     pub video: Video,
     pub accumulated_cycles: usize,
+    // A new picture is available (a frame was completed, or the LCD was turned off)
     pub update_buffer: bool,
     pub debug_mode: bool,
+    renderer: Renderer,
+    pixel_fifo: PixelFifo,
+    // HBLANK takes what mode 3 leaves of the line
+    hblank_threshold: usize,
+    // The STAT interrupt line: the OR of the enabled STAT sources
+    stat_line: bool,
+    lcd_enabled: bool,
+    // The first frame after turning the LCD on is not displayed
+    skip_frame: bool,
 }
 
 impl PPU {
@@ -46,9 +68,23 @@ impl PPU {
             accumulated_cycles: 0,
             update_buffer: true,
             debug_mode: debug_mode,
+            renderer: Renderer::Scanline,
+            pixel_fifo: PixelFifo::new(),
+            hblank_threshold: MODE00_THRESHOLD,
+            stat_line: false,
+            lcd_enabled: false,
+            skip_frame: false,
         }
     }
 
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+    }
+
+    pub fn renderer(&self) -> Renderer {
+        self.renderer
+    }
+
     // The `cycle` function uses the CPU cycles to select the right Mode.
     // Basically, it cycles through the following Modes
     //     ((10 -> 11 -> 00)+ -> 11 ->)+
@@ -62,78 +98,95 @@ impl PPU {
     pub fn cycle(&mut self, memory: &mut Memory, cycles: usize) {
         // Check if the display is enabled from the LCDC flag (at RAM)
         if (self.fetch_data(memory, memory_map::LCDC) & 0x80) == 0x00 {
+            if self.lcd_enabled {
+                self.disable_lcd(memory);
+            }
             return;
         }
+        if !self.lcd_enabled {
+            self.enable_lcd(memory);
+        }
 
         let current_stat: u8 = self.fetch_data(memory, memory_map::STAT);
-        let clean_stat: u8 = 0xFC & current_stat; // 0b11111100 & current_stat
         let mut current_ly: u8 = self.fetch_data(memory, memory_map::LY);
-        let current_lyc: u8 = self.fetch_data(memory, memory_map::LYC);
         let current_mode: u8 = current_stat & 0x03; // last 2 bits
 
         let mut next_mode: u8 = current_mode;
-        let mut next_stat: u8 = current_stat;
-        let mut must_request_interrupt: bool = false;
 
         self.accumulated_cycles += cycles;
 
         match current_mode {
             // This handle Mode 01 | Mode VBLANK
             current_mode if current_mode == video_mode::VBLANK => {
-                if self.accumulated_cycles > MODE01_THRESHOLD || current_ly >= FRAME_HEIGHT_FULL as u8 {
+                if self.accumulated_cycles >= MODE01_THRESHOLD || current_ly >= FRAME_HEIGHT_FULL as u8 {
                     // Request SEARCH_OAM
                     next_mode = video_mode::SEARCH_OAM;
-                    next_stat = (clean_stat | video_mode::SEARCH_OAM) & 0xFF;
-                    self.accumulated_cycles = 0;
-                    memory.write(memory_map::LY, 0x00);
+                    self.accumulated_cycles = self.accumulated_cycles.saturating_sub(MODE01_THRESHOLD);
+                    current_ly = 0x00;
+                    self.write_data(memory, memory_map::LY, current_ly);
                 } else {
                     // We must update the LY inside the VBLANK according to the VBLANK step
                     let ly_checker = (self.accumulated_cycles / VBLANK_CYCLES_PER_LINE) as u8;
                     current_ly = FRAME_HEIGHT as u8 + ly_checker;
-                    memory.write(memory_map::LY, current_ly);
+                    self.write_data(memory, memory_map::LY, current_ly);
                 }
             },
             // This handle Mode 10 | Mode SEARCH_OAM
             current_mode if current_mode == video_mode::SEARCH_OAM => {
-                if self.accumulated_cycles > MODE10_THRESHOLD {
+                if self.accumulated_cycles >= MODE10_THRESHOLD {
+                    // Select the objects of the line before drawing it
+                    self.video.search_oam(memory);
+                    if self.renderer == Renderer::PixelFifo {
+                        self.pixel_fifo.start_line(memory, &mut self.video);
+                    }
                     // Request SCANLINE
                     next_mode = video_mode::SCANLINE;
-                    next_stat = (clean_stat | video_mode::SCANLINE) & 0xFF;
-                    self.accumulated_cycles = 0;
+                    self.accumulated_cycles -= MODE10_THRESHOLD;
                 }
             },
             // This handle Mode 11 | Mode SCANLINE
             current_mode if current_mode == video_mode::SCANLINE => {
-                if self.accumulated_cycles > MODE11_THRESHOLD {
+                let line_finished: bool = match self.renderer {
+                    Renderer::Scanline => self.accumulated_cycles >= MODE11_THRESHOLD,
+                    Renderer::PixelFifo => self.pixel_fifo.advance(memory, &mut self.video, cycles * T_CYCLES_PER_M_CYCLE),
+                };
+                if line_finished {
                     // Request HBLANK
                     next_mode = video_mode::HBLANK;
-                    next_stat = (clean_stat | video_mode::HBLANK) & 0xFF;
-                    self.accumulated_cycles = 0;
-                    // It is the end of the scanline, so we can request to update the whole line
-                    self.video.update_scanline(memory);
-                    current_ly = self.fetch_data(memory, memory_map::LY);
-                    // If bit #3 at STAT is set, request interrupt
-                    must_request_interrupt = bit_operations::simple_bit(next_stat, 3);
+                    match self.renderer {
+                        Renderer::Scanline => {
+                            // It is the end of the scanline, so we can request to update the whole line
+                            self.video.update_scanline(memory);
+                            self.hblank_threshold = MODE00_THRESHOLD;
+                            self.accumulated_cycles -= MODE11_THRESHOLD;
+                        },
+                        Renderer::PixelFifo => {
+                            // The line is already drawn; HBLANK takes what mode 3 left of the line
+                            self.hblank_threshold = (MODE00_THRESHOLD + MODE11_THRESHOLD).saturating_sub(self.accumulated_cycles);
+                            self.accumulated_cycles = 0;
+                        },
+                    }
                 }
             },
             // This handle Mode 00 | Mode HBLANK
             current_mode if current_mode == video_mode::HBLANK => {
-                if self.accumulated_cycles > MODE00_THRESHOLD {
+                if self.accumulated_cycles >= self.hblank_threshold {
+                    // The next line starts
+                    current_ly += 1;
+                    self.write_data(memory, memory_map::LY, current_ly);
                     if current_ly >= FRAME_HEIGHT as u8 {
                         // Request VBLANK
                         next_mode = video_mode::VBLANK;
-                        next_stat = (clean_stat | video_mode::VBLANK) & 0xFF;
-                        // If bit #4 at STAT is set, request interrupt
-                        must_request_interrupt = bit_operations::simple_bit(next_stat, 4);
                         request_interrupt(self, memory, InterruptFlag::VBLANK);
+                        match self.skip_frame {
+                            true  => self.skip_frame = false,
+                            false => self.update_buffer = true,
+                        }
                     } else {
                         // Request SEARCH_OAM
                         next_mode = video_mode::SEARCH_OAM;
-                        next_stat = (clean_stat | video_mode::SEARCH_OAM) & 0xFF;
-                        // If bit #5 at STAT is set, request interrupt
-                        must_request_interrupt = bit_operations::simple_bit(next_stat, 5);
                     }
-                    self.accumulated_cycles = 0;
+                    self.accumulated_cycles -= self.hblank_threshold;
                 }
             },
             _ => panic!("Oops!... there's a bug at the PPU; mode: {:#04X}", current_mode),
@@ -143,20 +196,48 @@ impl PPU {
             current_mode, next_mode, current_ly),
                 self.debug_mode);
 
-        if must_request_interrupt && (next_mode != current_mode) {
-            request_interrupt(self, memory, InterruptFlag::LCDC);
-        }
+        // LY is compared to LYC as it changes, so the Match Flag holds for the whole line
+        let lyc_match: bool = current_ly == self.fetch_data(memory, memory_map::LYC);
+        memory.set_lcd_status(next_mode, lyc_match);
+        self.update_stat_line(memory, current_stat, next_mode, lyc_match);
+    }
 
-        if current_ly == current_lyc {
-            next_stat = next_stat | 0x04; // Set Match Flag (LYC = LCDCLY)
-            if bit_operations::simple_bit(next_stat, 6) {
-                request_interrupt(self, memory, InterruptFlag::LCDC);
-            }
-        } else {
-            next_stat = next_stat & !(0x40 as u8); // Reset Match Flag (LYC != LCDCLY)
+    // Turning the LCD off stops the PPU at line 0 (mode 0), and blanks the screen
+    fn disable_lcd(&mut self, memory: &mut Memory) {
+        self.lcd_enabled = false;
+        self.accumulated_cycles = 0;
+        self.stat_line = false;
+        self.write_data(memory, memory_map::LY, 0x00);
+        let lyc_match: bool = self.fetch_data(memory, memory_map::LYC) == 0x00;
+        memory.set_lcd_status(video_mode::HBLANK, lyc_match);
+        for pixel in self.video.frame_buffer.iter_mut() {
+            *pixel = 0x00;
         }
+        self.update_buffer = true;
+    }
+
+    // Turning the LCD on starts a new frame, which the LCD doesn't display
+    fn enable_lcd(&mut self, memory: &mut Memory) {
+        self.lcd_enabled = true;
+        self.skip_frame = true;
+        self.accumulated_cycles = 0;
+        self.write_data(memory, memory_map::LY, 0x00);
+        let lyc_match: bool = self.fetch_data(memory, memory_map::LYC) == 0x00;
+        memory.set_lcd_status(video_mode::SEARCH_OAM, lyc_match);
+    }
 
-        self.write_data(memory, memory_map::STAT, next_stat);
+    // The STAT sources (mode 0, 1, 2 and the LYC match) share a single interrupt line, and
+    // the LCDC interrupt is only requested when it goes high: while a source holds the
+    // line high, the others can't request it ("STAT blocking")
+    fn update_stat_line(&mut self, memory: &mut Memory, stat: u8, mode: u8, lyc_match: bool) {
+        let stat_line: bool = (mode == video_mode::HBLANK && bit_operations::simple_bit(stat, 3))
+            || (mode == video_mode::VBLANK && bit_operations::simple_bit(stat, 4))
+            || (mode == video_mode::SEARCH_OAM && bit_operations::simple_bit(stat, 5))
+            || (lyc_match && bit_operations::simple_bit(stat, 6));
+        if stat_line && !self.stat_line {
+            request_interrupt(self, memory, InterruptFlag::LCDC);
+        }
+        self.stat_line = stat_line;
     }
 
     pub fn fetch_data(&mut self, memory: &mut Memory, address: u16) -> u8 {
@@ -0,0 +1,195 @@
+/// The Real Time Clock (RTC) found in MBC3 cartridges
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SECONDS    : u8 = 0x08;
+pub const MINUTES    : u8 = 0x09;
+pub const HOURS      : u8 = 0x0A;
+pub const DAYS_LOW   : u8 = 0x0B;
+pub const DAYS_HIGH  : u8 = 0x0C;
+
+/// Size of the RTC block appended to the save RAM (as used by VBA-M, BGB and others):
+/// 5 current registers and 5 latched registers as 32-bit words, then a 64-bit timestamp
+pub const SAVE_STATE_SIZE        : usize = 48;
+/// Older emulators store the timestamp as a 32-bit word
+pub const SAVE_STATE_SIZE_LEGACY : usize = 44;
+
+const DAYS_HIGH_MSB   : u8 = 0x01; // Bit 8 of the Day Counter
+const DAYS_HIGH_HALT  : u8 = 0x40; // 0: active; 1: stop timer
+const DAYS_HIGH_CARRY : u8 = 0x80; // Day Counter overflow
+
+/// The host time the RTC advances from. It is a trait so the clock can be replaced by
+/// a deterministic one (e.g. for testing purposes).
+pub trait ClockSource {
+    /// Seconds elapsed since a fixed point in time (e.g. the UNIX epoch)
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock;
+
+impl ClockSource for SystemClock {
+
+    fn now(&self) -> u64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_error) => 0,
+        }
+    }
+
+}
+
+/// # The RTC Registers
+///
+/// 08h  RTC S   Seconds   0-59 (0-3Bh)
+/// 09h  RTC M   Minutes   0-59 (0-3Bh)
+/// 0Ah  RTC H   Hours     0-23 (0-17h)
+/// 0Bh  RTC DL  Lower 8 bits of Day Counter (0-FFh)
+/// 0Ch  RTC DH  Upper 1 bit of Day Counter, Carry Bit, Halt Flag
+///
+/// The registers are read through a latched copy: writing 00h and then 01h to the
+/// Latch Clock Data area (6000-7FFF) copies the running clock into the latched one.
+pub struct RealTimeClock {
+    clock: Box<dyn ClockSource>,
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    days: u16,
+    halted: bool,
+    carry: bool,
+    latched: [u8; 5],
+    latch_armed: bool,
+    last_update: u64,
+}
+
+impl RealTimeClock {
+
+    pub fn new(clock: Box<dyn ClockSource>) -> RealTimeClock {
+        let now: u64 = clock.now();
+
+        RealTimeClock {
+            clock: clock,
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            days: 0,
+            halted: false,
+            carry: false,
+            latched: [0; 5],
+            latch_armed: false,
+            last_update: now,
+        }
+    }
+
+    /// Reads the latched value for a given RTC register (08h-0Ch)
+    pub fn read(&self, register: u8) -> u8 {
+        match register {
+            SECONDS..=DAYS_HIGH => self.latched[(register - SECONDS) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    /// Writes directly to the running clock; returns whether its registers changed
+    pub fn write(&mut self, register: u8, word: u8) -> bool {
+        self.update();
+        let registers: [u8; 5] = self.registers();
+        match register {
+            SECONDS   => self.seconds = word & 0x3F,
+            MINUTES   => self.minutes = word & 0x3F,
+            HOURS     => self.hours = word & 0x1F,
+            DAYS_LOW  => self.days = (self.days & 0x0100) | word as u16,
+            DAYS_HIGH => {
+                self.days = (self.days & 0x00FF) | (((word & DAYS_HIGH_MSB) as u16) << 8);
+                self.halted = (word & DAYS_HIGH_HALT) != 0;
+                self.carry = (word & DAYS_HIGH_CARRY) != 0;
+            },
+            _ => {},
+        }
+        self.registers() != registers
+    }
+
+    /// Handles writes to the Latch Clock Data area: the 00h -> 01h sequence latches the clock
+    pub fn write_latch(&mut self, word: u8) {
+        if self.latch_armed && word == 0x01 {
+            self.latch();
+        }
+        self.latch_armed = word == 0x00;
+    }
+
+    pub fn latch(&mut self) {
+        self.update();
+        self.latched = self.registers();
+    }
+
+    /// The running clock registers, in the 08h-0Ch order
+    pub fn registers(&self) -> [u8; 5] {
+        let mut days_high: u8 = ((self.days >> 8) as u8) & DAYS_HIGH_MSB;
+        if self.halted {
+            days_high |= DAYS_HIGH_HALT;
+        }
+        if self.carry {
+            days_high |= DAYS_HIGH_CARRY;
+        }
+        [self.seconds, self.minutes, self.hours, (self.days & 0x00FF) as u8, days_high]
+    }
+
+    /// Brings the running clock up to date with the host clock
+    pub fn update(&mut self) {
+        let now: u64 = self.clock.now();
+        if !self.halted && now > self.last_update {
+            self.advance(now - self.last_update);
+        }
+        self.last_update = now;
+    }
+
+    fn advance(&mut self, elapsed_seconds: u64) {
+        let mut total: u64 = self.seconds as u64 + elapsed_seconds;
+        self.seconds = (total % 60) as u8;
+        total = total / 60 + self.minutes as u64;
+        self.minutes = (total % 60) as u8;
+        total = total / 60 + self.hours as u64;
+        self.hours = (total % 24) as u8;
+        total = total / 24 + self.days as u64;
+        if total > 0x01FF {
+            self.carry = true;
+        }
+        self.days = (total & 0x01FF) as u16;
+    }
+
+    /// The RTC block of a .sav file; all values are little-endian
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut state: Vec<u8> = Vec::with_capacity(SAVE_STATE_SIZE);
+        for register in self.registers().iter().chain(self.latched.iter()) {
+            state.extend_from_slice(&[*register, 0x00, 0x00, 0x00]);
+        }
+        for i in 0..8 {
+            state.push((self.last_update >> (i * 8)) as u8);
+        }
+        state
+    }
+
+    /// Restores the RTC block of a .sav file and advances the clock by the time elapsed
+    /// since it was saved
+    pub fn load_state(&mut self, state: &[u8]) {
+        if state.len() < SAVE_STATE_SIZE_LEGACY {
+            return;
+        }
+        let word = |index: usize| state[index * 4];
+        self.seconds = word(0) & 0x3F;
+        self.minutes = word(1) & 0x3F;
+        self.hours = word(2) & 0x1F;
+        self.days = word(3) as u16 | (((word(4) & DAYS_HIGH_MSB) as u16) << 8);
+        self.halted = (word(4) & DAYS_HIGH_HALT) != 0;
+        self.carry = (word(4) & DAYS_HIGH_CARRY) != 0;
+        for i in 0..5 {
+            self.latched[i] = word(5 + i);
+        }
+        let timestamp_size: usize = if state.len() >= SAVE_STATE_SIZE { 8 } else { 4 };
+        let mut timestamp: u64 = 0;
+        for i in 0..timestamp_size {
+            timestamp |= (state[40 + i] as u64) << (i * 8);
+        }
+        self.last_update = timestamp;
+        self.update();
+    }
+
+}
@@ -1,41 +1,83 @@
-use super::cpu::CPU;
-use super::ppu::PPU;
+use std::io;
+
+use super::cpu::{CPU, CPURegisters};
+use super::ppu::{PPU, Renderer};
 use super::timer::Timer;
 use super::memory::Memory;
+use super::bus::SystemBus;
+use super::cartridge_header::CartridgeHeader;
+use super::boot_rom::BootRom;
+use super::joypad::Button;
 use super::memory_map;
+use super::apu;
+use super::wav::{WavWriter, WavOutput};
+
+/// Machine cycles in a frame; `run_frame` paces the emulation (and the audio) with it, as
+/// the CPU counts machine cycles rather than clock cycles
+pub const CYCLES_PER_FRAME : usize = 17_556; // 70224 (4.194304 MHz div 59.7275 Hz) div 4
 
 pub struct System {
     cpu: CPU,
     ppu: PPU,
     timer: Timer,
     memory: Memory,
+    audio_recording: Option<WavWriter<Box<dyn WavOutput>>>,
+    // The first error while writing the recording; reported when it stops
+    audio_recording_error: Option<io::Error>,
 }
 
 impl System {
 
-    pub fn new(file_path: String) -> System {
+    /// Without a BOOT ROM, the system starts at 0x0100 with the post-boot state
+    pub fn new(file_path: String, boot_rom: Option<BootRom>) -> System {
         let cpu = CPU::new();
         let ppu = PPU::new();
         let timer = Timer::new();
-        let memory = Memory::new(file_path.to_owned());
+        let memory = Memory::new(file_path.to_owned(), boot_rom);
 
         System {
             cpu: cpu,
             ppu: ppu,
             timer: timer,
             memory: memory,
+            audio_recording: None,
+            audio_recording_error: None,
         }
     }
 
     pub fn boot(&mut self) {
-        self.cpu.boot();
+        if self.memory.boot_rom_enabled() {
+            self.cpu.boot();
+        } else {
+            self.cpu.boot_expected();
+            self.memory.post_boot();
+        }
     }
 
     pub fn cycle(&mut self) -> usize {
-        let cpu_cycles: usize = self.cpu.cycle(&mut self.memory);
-        self.timer.updater(&mut self.memory, cpu_cycles);
-        self.ppu.cycle(&mut self.memory, cpu_cycles);
-        cpu_cycles
+        // The timer and the PPU run along with each memory access of the instruction
+        let mut bus = SystemBus::new(&mut self.memory, &mut self.timer, Some(&mut self.ppu));
+        let cpu_cycles: usize = self.cpu.cycle(&mut bus);
+        bus.complete_instruction(cpu_cycles)
+    }
+
+    /// Runs the system for (at least) the machine cycles of a frame
+    pub fn run_frame(&mut self) -> usize {
+        let mut emulated_cycles: usize = 0;
+        while emulated_cycles < CYCLES_PER_FRAME {
+            emulated_cycles += self.cycle();
+        }
+        emulated_cycles
+    }
+
+    /// Selects the scanline (default) or the pixel FIFO renderer
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.ppu.set_renderer(renderer);
+    }
+
+    /// Lets the CPU access VRAM and OAM regardless of the PPU mode (for debugging)
+    pub fn set_access_restrictions(&mut self, enabled: bool) {
+        self.memory.set_access_restrictions(enabled);
     }
 
     pub fn video_buffer(&mut self) -> Vec<u8> {
@@ -43,12 +85,106 @@ impl System {
         buffer
     }
 
+    /// Whether there's a new picture to display (a frame was completed, or the LCD was
+    /// turned off) since the last call
+    pub fn frame_updated(&mut self) -> bool {
+        std::mem::replace(&mut self.ppu.update_buffer, false)
+    }
+
     pub fn video_mode(&mut self) -> u8 {
         (self.memory.fetch(memory_map::LCDC) & 0x03) & 0xFF
     }
 
+    /// The current audio output of the left and right terminals, between -1.0 and 1.0
+    pub fn audio_output(&self) -> (f32, f32) {
+        self.memory.audio_output()
+    }
+
+    /// Resamples the audio output to the host sample rate (0 disables it)
+    pub fn set_audio_sample_rate(&mut self, sample_rate: u32) {
+        self.memory.set_audio_sample_rate(sample_rate);
+    }
+
+    /// Interleaved stereo samples (left, right) produced since the last call, at the rate
+    /// given to `set_audio_sample_rate`
+    pub fn audio_samples(&mut self) -> Vec<f32> {
+        let samples: Vec<f32> = self.memory.audio_samples();
+        if let Some(ref mut recording) = self.audio_recording {
+            if let Err(error) = recording.write_samples(&samples) {
+                self.audio_recording_error.get_or_insert(error);
+            }
+        }
+        samples
+    }
+
+    /// Records the audio output to a 16-bit stereo WAV, at the current sample rate (or 48 kHz
+    /// when none is set). Samples are recorded as they are taken by `audio_samples`
+    pub fn start_audio_recording<W: WavOutput + 'static>(&mut self, output: W) -> io::Result<()> {
+        if self.memory.audio_sample_rate() == 0 {
+            self.memory.set_audio_sample_rate(apu::DEFAULT_SAMPLE_RATE);
+        }
+        let output: Box<dyn WavOutput> = Box::new(output);
+        self.audio_recording = Some(WavWriter::new(output, self.memory.audio_sample_rate())?);
+        self.audio_recording_error = None;
+        Ok(())
+    }
+
+    /// Completes the WAV file; returns the first error that happened while recording (if any)
+    pub fn stop_audio_recording(&mut self) -> io::Result<()> {
+        if let Some(recording) = self.audio_recording.take() {
+            recording.finish()?;
+        }
+        match self.audio_recording_error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_recording_audio(&self) -> bool {
+        self.audio_recording.is_some()
+    }
+
+    pub fn cpu_registers(&self) -> &CPURegisters {
+        &self.cpu.registers
+    }
+
+    /// Reads the memory map without spending cycles (for debugging)
+    pub fn fetch(&mut self, address: u16) -> u8 {
+        self.memory.fetch(address)
+    }
+
     pub fn has_stopped(&mut self) -> bool {
         self.cpu.stopped
     }
 
+    pub fn press(&mut self, button: Button) {
+        self.memory.press_button(button);
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.memory.release_button(button);
+    }
+
+    pub fn cartridge_header(&self) -> Option<&CartridgeHeader> {
+        self.memory.cartridge_header()
+    }
+
+    /// Battery-backed cartridge data (External RAM and RTC), in the raw .sav layout
+    pub fn export_save_ram(&self) -> Vec<u8> {
+        self.memory.export_save_ram()
+    }
+
+    pub fn import_save_ram(&mut self, data: &[u8]) {
+        self.memory.import_save_ram(data);
+    }
+
+    pub fn save_ram_dirty(&self) -> bool {
+        self.memory.save_ram_dirty()
+    }
+
+    /// Persists the battery-backed cartridge data to the .sav file next to the ROM
+    pub fn flush_save_ram(&mut self) -> io::Result<()> {
+        self.memory.flush_save_ram()
+    }
+
 }
@@ -26,6 +26,7 @@ impl Timer {
         }
     }
 
+    /// Runs the timer for the given clock cycles (4 per machine cycle)
     pub fn updater(&mut self, memory: &mut Memory, cycles: usize) {
         let timer_enabled: bool = (memory.fetch(memory_map::TAC) & 0x04) > 0;
         let timer_frequency_control: u8 = memory.fetch(memory_map::TAC) & 0x03;
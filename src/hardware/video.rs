@@ -20,9 +20,47 @@ const CHARACTER_DATA1    : u16 = 0x8000; // 8000h-8FFFh
 const TILE_ROW_PIXELS    : u16 = 16; // (or 2 bytes of data for each tile row)
 const TILE_ROW_OFFSET    : u16 = 2;
 
+const WINDOW_X_OFFSET    : i16 = 7;
+const WINDOW_X_MAX       : u8 = 166;
+
+const OBJECTS            : u16 = 40;
+const OBJECTS_PER_LINE   : usize = 10;
+const OBJECT_SIZE        : u16 = 4; // bytes per OAM entry
+const OBJECT_Y_OFFSET    : i16 = 16;
+const OBJECT_X_OFFSET    : i16 = 8;
+
+/// # An OAM (Object Attribute Memory) Entry
+///
+/// Byte 0  Y position + 16
+/// Byte 1  X position + 8
+/// Byte 2  Character Code (the lowest bit is ignored for 8x16 objects)
+/// Byte 3  Attributes
+///         Bit 7  BG and Window over OBJ (colors 1-3 of the BG hide the object)
+///         Bit 6  Vertical flip
+///         Bit 5  Horizontal flip
+///         Bit 4  Palette (OBP0 or OBP1)
+#[derive(Clone, Copy)]
+pub struct Object {
+    pub y: u8,
+    pub x: u8,
+    pub character_code: u8,
+    pub attributes: u8,
+    pub index: u8,
+}
+
 pub struct Video {
     pub frame_buffer: Vec<u8>,
     pub scanline_pixels_rendered: usize,
+    // The objects found by the OAM search for the current line
+    pub line_objects: Vec<Object>,
+    // The BG color numbers (0-3, before the palette) of the current line
+    background_line: Vec<u8>,
+    // The window keeps its own line counter, which only advances on lines it's drawn
+    pub window_line: u8,
+    // WY matched LY in this frame (the window may be drawn from then on)
+    pub window_triggered: bool,
+    // The previous line was drawn with WX=166: the window spans the whole line (DMG)
+    pub window_spans_line: bool,
 }
 
 impl Video {
@@ -33,6 +71,49 @@ impl Video {
         Video {
             frame_buffer: frame_buffer,
             scanline_pixels_rendered: 0,
+            line_objects: Vec::with_capacity(OBJECTS_PER_LINE),
+            background_line: vec![0; FRAME_WIDTH],
+            window_line: 0,
+            window_triggered: false,
+            window_spans_line: false,
+        }
+    }
+
+    /// The OAM search (mode 2): selects the first 10 objects (in OAM order) on the current
+    /// line; objects outside the screen horizontally are counted as well
+    pub fn search_oam(&mut self, memory: &mut Memory) {
+        let ly: i16 = memory.fetch(memory_map::LY) as i16;
+        let height: i16 = object_height(memory.fetch(memory_map::LCDC));
+
+        self.line_objects.clear();
+        for index in 0..OBJECTS {
+            let address: u16 = memory_map::OAM0 + index * OBJECT_SIZE;
+            let y: u8 = memory.fetch(address);
+            let top: i16 = y as i16 - OBJECT_Y_OFFSET;
+            if ly >= top && ly < top + height {
+                self.line_objects.push(Object {
+                    y: y,
+                    x: memory.fetch(address + 1),
+                    character_code: memory.fetch(address + 2),
+                    attributes: memory.fetch(address + 3),
+                    index: index as u8,
+                });
+                if self.line_objects.len() == OBJECTS_PER_LINE {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// WY is compared at the start of each line; a match enables the window until the next frame
+    pub fn start_line(&mut self, ly: u8, wy: u8) {
+        if ly == 0 {
+            self.window_line = 0;
+            self.window_triggered = false;
+            self.window_spans_line = false;
+        }
+        if ly == wy {
+            self.window_triggered = true;
         }
     }
 
@@ -41,13 +122,32 @@ impl Video {
         let updates: u8 = current_lcdc & 0x01;
 
         let current_ly = memory.fetch(memory_map::LY);
+        let current_wy = memory.fetch(memory_map::WY);
+        self.start_line(current_ly, current_wy);
 
         match updates {
             0x01 => self.update_background_actor(memory),
-            _ => {},
+            // With the BG disabled, the line is blank (color 0) and objects are always on top
+            _ => {
+                let line_start: usize = current_ly as usize * FRAME_WIDTH;
+                for pixel in self.frame_buffer[line_start..(line_start + FRAME_WIDTH)].iter_mut() {
+                    *pixel = 0x00;
+                }
+                for color_number in self.background_line.iter_mut() {
+                    *color_number = 0x00;
+                }
+            },
         };
 
-        memory.write(memory_map::LY, current_ly + 1)
+        // The window is hidden along with the BG (LCDC bit #0) on the DMG
+        match updates == 0x01 && bit_operations::simple_bit(current_lcdc, 5) && self.window_triggered {
+            true  => self.update_window_actor(memory),
+            false => self.window_spans_line = false,
+        }
+
+        if bit_operations::simple_bit(current_lcdc, 1) {
+            self.update_objects_actor(memory);
+        }
     }
 
     fn update_background_actor(&mut self, memory: &mut Memory) {
@@ -59,12 +159,13 @@ impl Video {
 
         let mut pixels_rendered: usize = 0;
 
-        // We walk through BG blocks instead of each single pixel
-        let y: u8 = (scy + ly) / TILE_DIMENSION;
-        for x in (scx / TILE_DIMENSION)..(scx / TILE_DIMENSION + 20) {
+        // We walk through BG blocks instead of each single pixel (21 blocks, since the first one
+        // may be partially scrolled out)
+        let y: u8 = scy.wrapping_add(ly) / TILE_DIMENSION;
+        for x in (scx / TILE_DIMENSION)..(scx / TILE_DIMENSION + 21) {
             // Since the BG may slide beyond its edges, we must sanitize the x,y values
-            let local_x = if x > BG_BLOCKS_PER_AXIS as u8 { x - BG_BLOCKS_PER_AXIS as u8 } else { x };
-            let local_y = if y > BG_BLOCKS_PER_AXIS as u8 { y - BG_BLOCKS_PER_AXIS as u8 } else { y };
+            let local_x = x % BG_BLOCKS_PER_AXIS as u8;
+            let local_y = y % BG_BLOCKS_PER_AXIS as u8;
             let block_id: u16 = ((local_y as u16 * BG_BLOCKS_PER_AXIS as u16) + local_x as u16) as u16;
             // Check the "BG Code Area Selection Flag" and obtain the BG Character Code
             let character_code: u8 = match bit_operations::simple_bit(lcdc, 3) {
@@ -73,27 +174,149 @@ impl Video {
             };
 
             // Obtain the tile row (y-axis) to render
-            let tile_row = (scy + ly) % TILE_DIMENSION;
+            let tile_row = scy.wrapping_add(ly) % TILE_DIMENSION;
             // Each BG Character Data (8x8 pixels Tile) is stored in 16 bytes, each 2 bytes representing a row
             // (the `TILE_ROW_PIXELS`). The "BG Character Data Selection Flag" establish the Character Data
             // Bank to obtain each word (each byte).
-            let character_data_offset: u16 = match bit_operations::simple_bit(lcdc, 4) {
-                true  => (CHARACTER_DATA1 + (character_code as u16 * TILE_ROW_PIXELS)),
-                false => ((CHARACTER_DATA0 + 0x800) as i32 + (character_code as i32 * TILE_ROW_PIXELS as i32)) as u16,
-            };
+            let character_data_offset: u16 = character_data_offset(lcdc, character_code);
             let lsb: u8 = memory.fetch(character_data_offset + (tile_row as u16 * TILE_ROW_OFFSET) as u16);
             let msb: u8 = memory.fetch(character_data_offset + (tile_row as u16 * TILE_ROW_OFFSET) as u16 + 1);
 
-            let tile_column: u8 = (pixels_rendered as u8 + scx) % TILE_DIMENSION;
+            let tile_column: u8 = (pixels_rendered as u8).wrapping_add(scx) % TILE_DIMENSION;
             // Render all the bits for the specific tile (x-axis) based on the previously selected row (y-axis)
             for column in (tile_column as u8)..8_u8 {
+                if pixels_rendered == FRAME_WIDTH {
+                    return;
+                }
                 let color = pixel_color(palette, lsb, msb, 7 - column);
                 self.frame_buffer[(ly as usize * FRAME_WIDTH + pixels_rendered)] = color;
+                self.background_line[pixels_rendered] = color_number(lsb, msb, 7 - column);
                 pixels_rendered += 1;
             }
         }
     }
 
+    /// Draws the window over the BG from the screen X position WX-7. With WX<7 the window
+    /// starts at the left edge, without its first 7-WX pixels; with WX=166 (DMG) only its
+    /// first column is visible, but the window spans the whole next line as well
+    fn update_window_actor(&mut self, memory: &mut Memory) {
+        let ly: u8 = memory.fetch(memory_map::LY);
+        let lcdc: u8 = memory.fetch(memory_map::LCDC);
+        let wx: u8 = memory.fetch(memory_map::WX);
+        let palette: u8 = memory.fetch(memory_map::BGP);
+
+        if wx > WINDOW_X_MAX && !self.window_spans_line {
+            return;
+        }
+
+        let start_x: i16 = match self.window_spans_line {
+            true  => 0,
+            false => wx as i16 - WINDOW_X_OFFSET,
+        };
+        self.window_spans_line = wx == WINDOW_X_MAX;
+
+        let code_area: u16 = match bit_operations::simple_bit(lcdc, 6) {
+            true  => CODE_AREA1,
+            false => CODE_AREA0,
+        };
+        let tile_y: u16 = (self.window_line / TILE_DIMENSION) as u16;
+        let tile_row: u16 = (self.window_line % TILE_DIMENSION) as u16;
+
+        for x in start_x.max(0)..(FRAME_WIDTH as i16) {
+            let window_x: u16 = (x - start_x) as u16;
+            let character_code: u8 = memory.fetch(code_area + tile_y * BG_BLOCKS_PER_AXIS + window_x / TILE_DIMENSION as u16);
+            let character_data_offset: u16 = character_data_offset(lcdc, character_code);
+            let lsb: u8 = memory.fetch(character_data_offset + tile_row * TILE_ROW_OFFSET);
+            let msb: u8 = memory.fetch(character_data_offset + tile_row * TILE_ROW_OFFSET + 1);
+            let pixel_x: u8 = 7 - (window_x % TILE_DIMENSION as u16) as u8;
+
+            self.frame_buffer[ly as usize * FRAME_WIDTH + x as usize] = pixel_color(palette, lsb, msb, pixel_x);
+            self.background_line[x as usize] = color_number(lsb, msb, pixel_x);
+        }
+
+        self.window_line = self.window_line.wrapping_add(1);
+    }
+
+    /// Draws the objects found by the OAM search. On the DMG, the object with the smallest
+    /// X position has priority (the first in OAM on a tie); its transparent pixels (color 0)
+    /// let the objects below it show through
+    fn update_objects_actor(&mut self, memory: &mut Memory) {
+        let ly: i16 = memory.fetch(memory_map::LY) as i16;
+        let lcdc: u8 = memory.fetch(memory_map::LCDC);
+        let height: i16 = object_height(lcdc);
+        let palettes: [u8; 2] = [memory.fetch(memory_map::OBP0), memory.fetch(memory_map::OBP1)];
+
+        let mut objects: Vec<Object> = self.line_objects.clone();
+        objects.sort_by_key(|object| (object.x, object.index));
+
+        // The color number of the winning object for each pixel, and its object
+        let mut line: Vec<Option<(u8, Object)>> = vec![None; FRAME_WIDTH];
+
+        for object in objects.iter().rev() {
+            let mut row: i16 = ly - (object.y as i16 - OBJECT_Y_OFFSET);
+            if bit_operations::simple_bit(object.attributes, 6) {
+                row = height - 1 - row;
+            }
+            let character_code: u8 = match height {
+                16 => object.character_code & 0xFE,
+                _  => object.character_code,
+            };
+            let character_data_offset: u16 = CHARACTER_DATA1 + character_code as u16 * TILE_ROW_PIXELS;
+            let lsb: u8 = memory.fetch(character_data_offset + row as u16 * TILE_ROW_OFFSET);
+            let msb: u8 = memory.fetch(character_data_offset + row as u16 * TILE_ROW_OFFSET + 1);
+
+            for column in 0..(TILE_DIMENSION as i16) {
+                let x: i16 = object.x as i16 - OBJECT_X_OFFSET + column;
+                if x < 0 || x >= FRAME_WIDTH as i16 {
+                    continue;
+                }
+                let pixel_x: u8 = match bit_operations::simple_bit(object.attributes, 5) {
+                    true  => column as u8,
+                    false => 7 - column as u8,
+                };
+                let color_number: u8 = color_number(lsb, msb, pixel_x);
+                if color_number != 0x00 {
+                    line[x as usize] = Some((color_number, *object));
+                }
+            }
+        }
+
+        for (x, pixel) in line.iter().enumerate() {
+            if let Some((color_number, object)) = *pixel {
+                let behind_background: bool = bit_operations::simple_bit(object.attributes, 7)
+                    && self.background_line[x] != 0x00;
+                if !behind_background {
+                    let palette: u8 = palettes[bit_operations::simple_bit(object.attributes, 4) as usize];
+                    self.frame_buffer[ly as usize * FRAME_WIDTH + x] = (palette >> (color_number * 2)) & 0x03;
+                }
+            }
+        }
+    }
+
+}
+
+/// The BG and window Character Data: 8000-8FFF (unsigned codes) when LCDC bit #4 is set;
+/// 8800-97FF (signed codes, from 9000) otherwise
+pub fn character_data_offset(lcdc: u8, character_code: u8) -> u16 {
+    match bit_operations::simple_bit(lcdc, 4) {
+        true  => CHARACTER_DATA1 + character_code as u16 * TILE_ROW_PIXELS,
+        false => ((CHARACTER_DATA0 + 0x800) as i32 + (character_code as i8) as i32 * TILE_ROW_PIXELS as i32) as u16,
+    }
+}
+
+/// Objects are 8x16 when LCDC bit #2 is set; 8x8 otherwise
+pub fn object_height(lcdc: u8) -> i16 {
+    match bit_operations::simple_bit(lcdc, 2) {
+        true  => 16,
+        false => 8,
+    }
+}
+
+/// The color number (0-3, before the palette) of a pixel in a tile row
+pub fn color_number(lsb: u8, msb: u8, pixel_x: u8) -> u8 {
+    let bit0 = if bit_operations::simple_bit(lsb, pixel_x as usize) { 0x01 } else { 0x00 } as u8;
+    let bit1 = if bit_operations::simple_bit(msb, pixel_x as usize) { 0x02 } else { 0x00 } as u8;
+    bit0 | bit1
 }
 
 fn pixel_color(palette: u8, lsb: u8, msb: u8, pixel_x: u8) -> u8 {
@@ -102,10 +325,7 @@ fn pixel_color(palette: u8, lsb: u8, msb: u8, pixel_x: u8) -> u8 {
     let shade_2 = (palette >> 4) & 0x03;
     let shade_3 = palette >> 6;
 
-    let bit0 = if bit_operations::simple_bit(lsb, pixel_x as usize) { 0x01 } else { 0x00 } as u8;
-    let bit1 = if bit_operations::simple_bit(msb, pixel_x as usize) { 0x02 } else { 0x00 } as u8;
-
-    match bit0 | bit1 {
+    match color_number(lsb, msb, pixel_x) {
         0x00 => shade_0,
         0x01 => shade_1,
         0x02 => shade_2,
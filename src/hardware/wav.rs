@@ -0,0 +1,74 @@
+/// WAV (RIFF, 16-bit PCM stereo) Writer for the APU output
+
+use std::io::prelude::*;
+use std::io::{self, SeekFrom};
+
+const HEADER_SIZE     : u32 = 44;
+const CHANNELS        : u16 = 2;
+const BITS_PER_SAMPLE : u16 = 16;
+
+/// Anything a recording can be written to (e.g. a `File` or a `Cursor<Vec<u8>>`)
+pub trait WavOutput: Write + Seek {}
+
+impl<T: Write + Seek> WavOutput for T {}
+
+/// Writes a header with empty sizes up front, and fills them in on `finish`
+pub struct WavWriter<W: Write + Seek> {
+    output: W,
+    // Position of the RIFF header within the output
+    start: u64,
+    data_size: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+
+    pub fn new(mut output: W, sample_rate: u32) -> io::Result<WavWriter<W>> {
+        let block_align: u16 = CHANNELS * BITS_PER_SAMPLE / 8;
+        let start: u64 = output.stream_position()?;
+
+        output.write_all(b"RIFF")?;
+        output.write_all(&(HEADER_SIZE - 8).to_le_bytes())?;
+        output.write_all(b"WAVE")?;
+        output.write_all(b"fmt ")?;
+        output.write_all(&16u32.to_le_bytes())?;
+        output.write_all(&1u16.to_le_bytes())?; // PCM
+        output.write_all(&CHANNELS.to_le_bytes())?;
+        output.write_all(&sample_rate.to_le_bytes())?;
+        output.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        output.write_all(&block_align.to_le_bytes())?;
+        output.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+        output.write_all(b"data")?;
+        output.write_all(&0u32.to_le_bytes())?;
+
+        Ok(WavWriter {
+            output: output,
+            start: start,
+            data_size: 0,
+        })
+    }
+
+    /// Writes interleaved stereo samples (left, right), between -1.0 and 1.0
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        let mut buffer: Vec<u8> = Vec::with_capacity(samples.len() * 2);
+        for &sample in samples {
+            let value: i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        self.output.write_all(&buffer)?;
+        self.data_size += buffer.len() as u32;
+        Ok(())
+    }
+
+    /// Fills in the RIFF and data chunk sizes, and hands the output back
+    pub fn finish(mut self) -> io::Result<W> {
+        let end: u64 = self.output.stream_position()?;
+        self.output.seek(SeekFrom::Start(self.start + 4))?;
+        self.output.write_all(&(HEADER_SIZE - 8 + self.data_size).to_le_bytes())?;
+        self.output.seek(SeekFrom::Start(self.start + HEADER_SIZE as u64 - 4))?;
+        self.output.write_all(&self.data_size.to_le_bytes())?;
+        self.output.seek(SeekFrom::Start(end))?;
+        self.output.flush()?;
+        Ok(self.output)
+    }
+
+}
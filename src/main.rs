@@ -5,12 +5,148 @@ extern crate sdl2;
 mod emulator;
 mod hardware;
 
+use std::collections::HashMap;
 use std::env;
-use emulator::context::EmulatorContext;
+use std::path::Path;
+use std::process;
+use emulator::context::{EmulatorContext, AudioMode};
+use emulator::input::InputBindings;
+use hardware::boot_rom::{BootRom, Model};
+use hardware::ppu::Renderer;
+
+const DEFAULT_BOOT_ROM : &str = "./data/DMG_ROM.bin";
+const DEFAULT_BINDINGS : &str = "./bindings.cfg";
+const DEFAULT_FRAMES   : usize = 3600; // A minute of emulation
+// Options followed by a value (e.g. `--model CGB`); any other option is a switch
+const VALUE_OPTIONS    : [&str; 5] = ["--boot-rom", "--model", "--bindings", "--frames", "--song"];
+const SWITCH_OPTIONS   : [&str; 5] = ["--no-boot-rom", "--no-audio", "--audio-sync", "--pixel-fifo", "--no-access-restrictions"];
+const USAGE            : &str = "\
+Usage: gbc [options] <rom>
+       gbc info <rom>
+       gbc record [options] <rom> <wav>
+Options: --boot-rom <path> --model <name> --bindings <path> --frames <n> --song <n>
+         --no-boot-rom --no-audio --audio-sync --pixel-fifo --no-access-restrictions";
 
 pub fn main() {
-    let mut args = env::args();
-    let rom_file_path = args.nth(1).expect("ROM filepath is not available; aborting");
-    let mut emulator = EmulatorContext::new(rom_file_path.to_owned());
-    emulator.run();
+    let (operands, options) = parse_arguments(env::args().skip(1).collect());
+    let mut operands = operands.into_iter();
+    let first_argument = operands.next().expect("ROM filepath is not available; aborting");
+
+    match first_argument.as_ref() {
+        "info" => {
+            let rom_file_path = operands.next().expect("ROM filepath is not available; aborting");
+            emulator::info::print(rom_file_path);
+        },
+        "record" => {
+            let rom_file_path = operands.next().expect("ROM filepath is not available; aborting");
+            let output_path = operands.next().expect("WAV filepath is not available; aborting");
+            let frames: usize = match options.get("--frames") {
+                Some(frames) => frames.parse().expect("Invalid number of frames; aborting"),
+                None => DEFAULT_FRAMES,
+            };
+            match is_gbs(&rom_file_path) {
+                true  => emulator::record::run_gbs(rom_file_path, song(&options), output_path, frames),
+                false => emulator::record::run_rom(rom_file_path, boot_rom(&options), output_path, frames),
+            }
+        },
+        _ if is_gbs(&first_argument) => {
+            emulator::gbs_player::run(first_argument.to_owned(), song(&options));
+        },
+        _ => {
+            let mut emulator = EmulatorContext::new(first_argument.to_owned(), boot_rom(&options), bindings(&options), audio_mode(&options));
+            if options.contains_key("--pixel-fifo") {
+                emulator.set_renderer(Renderer::PixelFifo);
+            }
+            if options.contains_key("--no-access-restrictions") {
+                emulator.set_access_restrictions(false);
+            }
+            emulator.run();
+        },
+    }
+}
+
+fn parse_arguments(args: Vec<String>) -> (Vec<String>, HashMap<String, String>) {
+    let mut operands: Vec<String> = Vec::new();
+    let mut options: HashMap<String, String> = HashMap::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if VALUE_OPTIONS.contains(&arg.as_ref()) {
+            let value = args.next().unwrap_or_else(|| usage_error(&format!("{} requires a value", arg)));
+            options.insert(arg, value);
+        } else if SWITCH_OPTIONS.contains(&arg.as_ref()) {
+            options.insert(arg, String::new());
+        } else if arg.starts_with("--") {
+            usage_error(&format!("Unknown option {}", arg));
+        } else {
+            operands.push(arg);
+        }
+    }
+
+    (operands, options)
+}
+
+/// Reports a command line mistake along with the usage, and exits with status 2
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn boot_rom(options: &HashMap<String, String>) -> Option<BootRom> {
+    if options.contains_key("--no-boot-rom") {
+        return None;
+    }
+
+    let model = match options.get("--model") {
+        Some(name) => Model::from_name(name).expect("Unknown GAME BOY model (DMG0, DMG, MGB, SGB, SGB2, CGB0 or CGB); aborting"),
+        None => Model::DMG,
+    };
+    let file_path: &str = match options.get("--boot-rom") {
+        Some(file_path) => file_path,
+        None => DEFAULT_BOOT_ROM,
+    };
+
+    match BootRom::from_file(file_path, model) {
+        Ok(boot_rom) => Some(boot_rom),
+        Err(error) => panic!("Could not load BOOT ROM file {} ({}); it may be skipped with --no-boot-rom; aborting",
+            file_path, error),
+    }
+}
+
+/// Input bindings from `--bindings <path>` or `./bindings.cfg` (if any); defaults otherwise
+fn bindings(options: &HashMap<String, String>) -> InputBindings {
+    let file_path: &str = match options.get("--bindings") {
+        Some(file_path) => file_path,
+        None if Path::new(DEFAULT_BINDINGS).exists() => DEFAULT_BINDINGS,
+        None => return InputBindings::new(),
+    };
+
+    match InputBindings::from_file(file_path) {
+        Ok(bindings) => bindings,
+        Err(error) => panic!("Could not load input bindings ({}); aborting", error),
+    }
+}
+
+/// `--no-audio` mutes the emulator; `--audio-sync` lets the audio device drive the pace
+fn audio_mode(options: &HashMap<String, String>) -> AudioMode {
+    match (options.contains_key("--no-audio"), options.contains_key("--audio-sync")) {
+        (true, _)      => AudioMode::Disabled,
+        (false, true)  => AudioMode::Synced,
+        (false, false) => AudioMode::Enabled,
+    }
+}
+
+fn is_gbs(file_path: &str) -> bool {
+    match Path::new(file_path).extension() {
+        Some(extension) => extension.to_string_lossy().to_lowercase() == "gbs",
+        None => false,
+    }
+}
+
+/// The GBS song from `--song <N>` (1-based), as the 0-based song number
+fn song(options: &HashMap<String, String>) -> Option<u8> {
+    options.get("--song").map(|song| {
+        let song: u8 = song.parse().expect("Invalid song number; aborting");
+        song.max(1) - 1
+    })
 }
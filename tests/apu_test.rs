@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::apu::*;
+    use tests::oh_boy::hardware::memory_map;
+
+    // Frame sequencer period, in machine cycles
+    const FRAME_STEP: usize = 2048;
+
+    #[test]
+    fn test_read_masks() {
+        let mut apu = APU::new();
+        assert_eq!(apu.read(memory_map::NR52), 0x70);
+        // Writes are ignored while powered off
+        apu.write(memory_map::NR50, 0x77);
+        assert_eq!(apu.read(memory_map::NR50), 0x00);
+
+        apu.write(memory_map::NR52, 0x80);
+        assert_eq!(apu.read(memory_map::NR52), 0xF0);
+        apu.write(memory_map::NR10, 0x00);
+        assert_eq!(apu.read(memory_map::NR10), 0x80);
+        apu.write(memory_map::NR11, 0x80);
+        assert_eq!(apu.read(memory_map::NR11), 0xBF);
+        apu.write(memory_map::NR13, 0x12);
+        assert_eq!(apu.read(memory_map::NR13), 0xFF);
+        apu.write(memory_map::NR30, 0x00);
+        assert_eq!(apu.read(memory_map::NR30), 0x7F);
+        apu.write(memory_map::NR50, 0x77);
+        assert_eq!(apu.read(memory_map::NR50), 0x77);
+        assert_eq!(apu.read(0xFF27), 0xFF);
+
+        // Wave RAM is kept when powering off; the registers are cleared
+        apu.write(memory_map::WPR0, 0xA5);
+        apu.write(memory_map::NR52, 0x00);
+        assert_eq!(apu.read(memory_map::NR50), 0x00);
+        assert_eq!(apu.read(memory_map::WPR0), 0xA5);
+    }
+
+    #[test]
+    fn test_trigger_and_dac() {
+        let mut apu = APU::new();
+        apu.write(memory_map::NR52, 0x80);
+        // The DAC is off: triggering doesn't enable the channel
+        apu.write(memory_map::NR22, 0x00);
+        apu.write(memory_map::NR24, 0x80);
+        assert_eq!(apu.read(memory_map::NR52) & 0x0F, 0x00);
+
+        apu.write(memory_map::NR22, 0xF0);
+        apu.write(memory_map::NR24, 0x80);
+        assert_eq!(apu.read(memory_map::NR52) & 0x0F, 0x02);
+        // Turning the DAC off disables the channel
+        apu.write(memory_map::NR22, 0x00);
+        assert_eq!(apu.read(memory_map::NR52) & 0x0F, 0x00);
+
+        apu.write(memory_map::NR30, 0x80);
+        apu.write(memory_map::NR34, 0x80);
+        apu.write(memory_map::NR42, 0x08);
+        apu.write(memory_map::NR44, 0x80);
+        assert_eq!(apu.read(memory_map::NR52) & 0x0F, 0x0C);
+    }
+
+    #[test]
+    fn test_length_counter() {
+        let mut apu = APU::new();
+        apu.write(memory_map::NR52, 0x80);
+        apu.write(memory_map::NR12, 0xF0);
+        // 4 steps of length (at 256 Hz)
+        apu.write(memory_map::NR11, 0x3C);
+        apu.write(memory_map::NR14, 0xC0);
+        assert_eq!(apu.read(memory_map::NR52) & 0x01, 0x01);
+
+        // Length is clocked on steps 0, 2, 4 and 6
+        apu.tick(FRAME_STEP * 6);
+        assert_eq!(apu.read(memory_map::NR52) & 0x01, 0x01);
+        apu.tick(FRAME_STEP * 2);
+        assert_eq!(apu.read(memory_map::NR52) & 0x01, 0x00);
+    }
+
+    #[test]
+    fn test_sweep_overflow() {
+        let mut apu = APU::new();
+        apu.write(memory_map::NR52, 0x80);
+        apu.write(memory_map::NR12, 0xF0);
+        // Period 1, addition, shift 1: 0x700 + 0x380 overflows on trigger
+        apu.write(memory_map::NR10, 0x11);
+        apu.write(memory_map::NR13, 0x00);
+        apu.write(memory_map::NR14, 0x87);
+        assert_eq!(apu.read(memory_map::NR52) & 0x01, 0x00);
+
+        // 0x400 + 0x200 is fine, but the next calculation overflows on the first sweep clock
+        apu.write(memory_map::NR14, 0x84);
+        assert_eq!(apu.read(memory_map::NR52) & 0x01, 0x01);
+        apu.tick(FRAME_STEP * 3);
+        assert_eq!(apu.read(memory_map::NR52) & 0x01, 0x00);
+    }
+
+    #[test]
+    fn test_mixing() {
+        let mut apu = APU::new();
+        assert_eq!(apu.output(), (0.0, 0.0));
+        apu.write(memory_map::NR52, 0x80);
+        apu.write(memory_map::NR50, 0x77);
+        // Only the noise channel, on the left terminal
+        apu.write(memory_map::NR51, 0x80);
+        apu.write(memory_map::NR42, 0xF0);
+        apu.write(memory_map::NR44, 0x80);
+        let (left, right) = apu.output();
+        assert!(left != 0.0);
+        assert_eq!(right, 0.0);
+    }
+
+    #[test]
+    fn test_resampling() {
+        let mut apu = APU::new();
+        assert!(apu.take_samples().is_empty());
+
+        // A second of emulation, at 48 kHz (silence while powered off)
+        apu.set_sample_rate(48_000);
+        apu.tick(1_048_576);
+        let samples = apu.take_samples();
+        assert_eq!(samples.len(), 2 * 48_000);
+        assert!(samples.iter().all(|&sample| sample == 0.0));
+        assert!(apu.take_samples().is_empty());
+    }
+}
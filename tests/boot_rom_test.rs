@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::boot_rom::*;
+    use tests::oh_boy::hardware::system::System;
+    use tests::oh_boy::hardware::memory_map;
+
+    #[test]
+    fn test_sizes() {
+        assert!(BootRom::from_bytes(vec![0; 256], Model::DMG).is_ok());
+        assert!(BootRom::from_bytes(vec![0; 256], Model::SGB2).is_ok());
+        assert!(BootRom::from_bytes(vec![0; 2304], Model::CGB).is_ok());
+        assert!(BootRom::from_bytes(vec![0; 256], Model::CGB0).is_err());
+        assert!(BootRom::from_bytes(vec![0; 2304], Model::MGB).is_err());
+    }
+
+    #[test]
+    fn test_mapping() {
+        let dmg = BootRom::from_bytes(vec![0; 256], Model::DMG).unwrap();
+        assert!(dmg.maps(0x00FF));
+        assert!(!dmg.maps(0x0100));
+        assert!(!dmg.maps(0x0200));
+
+        // The cartridge header stays visible through the CGB BOOT ROM gap
+        let cgb = BootRom::from_bytes(vec![0; 2304], Model::CGB).unwrap();
+        assert!(cgb.maps(0x00FF));
+        assert!(!cgb.maps(0x0100));
+        assert!(!cgb.maps(0x01FF));
+        assert!(cgb.maps(0x0200));
+        assert!(cgb.maps(0x08FF));
+        assert!(!cgb.maps(0x0900));
+    }
+
+    #[test]
+    fn test_model_names() {
+        assert_eq!(Model::from_name("cgb0"), Some(Model::CGB0));
+        assert_eq!(Model::from_name("SGB2"), Some(Model::SGB2));
+        assert_eq!(Model::from_name("GBA"), None);
+    }
+
+    #[test]
+    fn test_post_boot_state() {
+        let mut system = System::new("./data/nintendo-logo.gb".to_owned(), None);
+        system.boot();
+        {
+            let registers = system.cpu_registers();
+            assert_eq!(registers.program_counter, 0x0100);
+            assert_eq!(registers.stack_pointer, 0xFFFE);
+            assert_eq!((registers.r_a, registers.r_f), (0x01, 0xB0));
+            assert_eq!((registers.r_b, registers.r_c), (0x00, 0x13));
+            assert_eq!((registers.r_d, registers.r_e), (0x00, 0xD8));
+            assert_eq!((registers.r_h, registers.r_l), (0x01, 0x4D));
+        }
+
+        let io_registers: [(u16, u8); 14] = [
+            (memory_map::P1,   0xCF),
+            (memory_map::DIV,  0xAB),
+            (memory_map::TAC,  0xF8),
+            (memory_map::IF,   0xE1),
+            (memory_map::LCDC, 0x91),
+            (memory_map::STAT, 0x85),
+            (memory_map::LY,   0x00),
+            (memory_map::BGP,  0xFC),
+            (memory_map::OBP0, 0xFF),
+            (memory_map::DMGS, 0x01),
+            (memory_map::NR10, 0x80),
+            (memory_map::NR50, 0x77),
+            (memory_map::NR51, 0xF3),
+            (memory_map::NR52, 0xF1),
+        ];
+        for &(address, word) in io_registers.iter() {
+            assert_eq!(system.fetch(address), word, "{:#06X}", address);
+        }
+        // The BOOT ROM is unmapped: the cartridge shows at 0x0000
+        assert_eq!(system.fetch(0x0000), 0xC3);
+        assert_eq!(system.fetch(0x0104), 0xCE);
+    }
+}
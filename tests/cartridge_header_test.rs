@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::cartridge_header::*;
+
+    use std::fs::File;
+    use std::io::prelude::*;
+
+    fn nintendo_logo_rom() -> Vec<u8> {
+        let mut rom: Vec<u8> = Vec::new();
+        File::open("./data/nintendo-logo.gb").unwrap().read_to_end(&mut rom).unwrap();
+        rom
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut rom: Vec<u8> = vec![0; 0x8000];
+        rom[0x0104..0x0134].copy_from_slice(&NINTENDO_LOGO);
+        rom[0x0134..0x0139].copy_from_slice(b"TETRA");
+        rom[0x0143] = 0x80;
+        rom[0x0144] = b'0';
+        rom[0x0145] = b'1';
+        rom[0x0146] = 0x03;
+        rom[0x0147] = 0x13;
+        rom[0x0148] = 0x05;
+        rom[0x0149] = 0x03;
+        rom[0x014A] = 0x01;
+        rom[0x014B] = 0x33;
+        rom[0x014C] = 0x02;
+        rom[0x014D] = header_checksum(&rom);
+
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert_eq!(header.title, "TETRA");
+        assert_eq!(header.color_support, ColorSupport::Supported);
+        assert!(header.sgb_support);
+        assert_eq!(header.licensee(), "Nintendo R&D1");
+        assert_eq!(header.cartridge_type_name(), "MBC3+RAM+BATTERY");
+        assert_eq!(header.rom_size(), 1024 * 1024);
+        assert_eq!(header.rom_banks(), 64);
+        assert_eq!(header.ram_size(), 32 * 1024);
+        assert_eq!(header.destination, Destination::Overseas);
+        assert_eq!(header.version, 0x02);
+        assert!(header.logo_matches);
+        assert!(header.is_valid());
+        assert!(!header.global_checksum_matches());
+    }
+
+    #[test]
+    fn test_checksums() {
+        let rom = nintendo_logo_rom();
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert!(header.logo_matches);
+        // The checksums stored at 014D and 014E-014F of the fixture
+        assert_eq!(header.header_checksum, 0xE6);
+        assert_eq!(header.computed_header_checksum, 0xE6);
+        assert_eq!(header.global_checksum, 0x31BB);
+        assert_eq!(header.computed_global_checksum, 0x31BB);
+        assert!(header.header_checksum_matches());
+        assert!(header.global_checksum_matches());
+
+        let mut corrupted = rom.clone();
+        corrupted[0x0134] = corrupted[0x0134].wrapping_add(1);
+        let corrupted_header = CartridgeHeader::parse(&corrupted).unwrap();
+        assert_eq!(corrupted_header.computed_header_checksum, 0xE5);
+        assert_eq!(corrupted_header.computed_global_checksum, 0x31BC);
+        assert!(!corrupted_header.header_checksum_matches());
+        assert!(!corrupted_header.global_checksum_matches());
+        assert!(!corrupted_header.is_valid());
+    }
+
+    #[test]
+    fn test_too_short() {
+        assert_eq!(CartridgeHeader::parse(&[0; 0x0100]).unwrap_err(), HeaderError::TooShort(0x0100));
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::cartridge::*;
+    use tests::oh_boy::hardware::cartridge_types;
+    use tests::oh_boy::hardware::memory_map;
+
+    /// Builds a ROM where the first byte of every bank (except bank #0) holds its number
+    fn rom(cartridge_type: u8, banks: usize, ram_size_code: u8) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; banks * ROM_BANK_SIZE];
+        for bank in 1..banks {
+            data[bank * ROM_BANK_SIZE] = bank as u8;
+            data[bank * ROM_BANK_SIZE + 1] = (bank >> 8) as u8;
+        }
+        data[memory_map::RTC as usize] = cartridge_type;
+        data[memory_map::ASIZ as usize] = ram_size_code;
+        data
+    }
+
+    #[test]
+    fn test_rom_only() {
+        let mut cartridge = from_rom(rom(cartridge_types::ROM_ONLY, 2, 0x00));
+        cartridge.write_rom(0x2000, 0x05);
+        assert_eq!(cartridge.read_rom(0x4000), 0x01);
+        assert_eq!(cartridge.read_ram(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn test_mbc1_rom_banking() {
+        let mut cartridge = from_rom(rom(cartridge_types::ROM_MBC1, 128, 0x00));
+        assert_eq!(cartridge.read_rom(0x4000), 0x01);
+        cartridge.write_rom(0x2000, 0x00);
+        assert_eq!(cartridge.read_rom(0x4000), 0x01);
+        cartridge.write_rom(0x2000, 0x05);
+        assert_eq!(cartridge.read_rom(0x4000), 0x05);
+        // Banks 0x20, 0x40 and 0x60 are read as 0x21, 0x41 and 0x61
+        cartridge.write_rom(0x2000, 0x00);
+        cartridge.write_rom(0x4000, 0x01);
+        assert_eq!(cartridge.read_rom(0x4000), 0x21);
+        // The advanced banking mode maps BANK2 at 0000-3FFF
+        assert_eq!(cartridge.read_rom(0x0000), 0x00);
+        cartridge.write_rom(0x6000, 0x01);
+        assert_eq!(cartridge.read_rom(0x0000), 0x20);
+    }
+
+    #[test]
+    fn test_mbc1_ram_banking() {
+        let mut cartridge = from_rom(rom(cartridge_types::ROM_MBC1_RAM1, 4, 0x03));
+        cartridge.write_ram(0xA000, 0x42);
+        assert_eq!(cartridge.read_ram(0xA000), 0xFF);
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x42);
+        assert_eq!(cartridge.read_ram(0xA000), 0x42);
+        // RAM banks are only switched in the advanced banking mode
+        cartridge.write_rom(0x4000, 0x02);
+        assert_eq!(cartridge.read_ram(0xA000), 0x42);
+        cartridge.write_rom(0x6000, 0x01);
+        assert_eq!(cartridge.read_ram(0xA000), 0x00);
+        assert_eq!(cartridge.save_data().len(), 0x8000);
+    }
+
+    #[test]
+    fn test_mbc2() {
+        let mut cartridge = from_rom(rom(cartridge_types::ROM_MBC2, 16, 0x00));
+        // Address bit 8 set: ROM Bank Number
+        cartridge.write_rom(0x2100, 0x03);
+        assert_eq!(cartridge.read_rom(0x4000), 0x03);
+        // Address bit 8 clear: RAM Enable
+        cartridge.write_rom(0x0100, 0x0A);
+        cartridge.write_ram(0xA000, 0x42);
+        assert_eq!(cartridge.read_ram(0xA000), 0xFF);
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x42);
+        assert_eq!(cartridge.read_ram(0xA000), 0xF2);
+        // The 512 half-bytes are echoed through A000-BFFF
+        assert_eq!(cartridge.read_ram(0xA200), 0xF2);
+    }
+
+    #[test]
+    fn test_mbc3() {
+        let mut cartridge = from_rom(rom(cartridge_types::ROM_MBC3_RAM, 128, 0x03));
+        cartridge.write_rom(0x2000, 0x7F);
+        assert_eq!(cartridge.read_rom(0x4000), 0x7F);
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_rom(0x4000, 0x03);
+        cartridge.write_ram(0xBFFF, 0x42);
+        assert_eq!(cartridge.read_ram(0xBFFF), 0x42);
+        cartridge.write_rom(0x4000, 0x00);
+        assert_eq!(cartridge.read_ram(0xBFFF), 0x00);
+    }
+
+    #[test]
+    fn test_mbc5() {
+        let mut cartridge = from_rom(rom(cartridge_types::ROM_MBC5_RUMBLE_RAM, 512, 0x04));
+        cartridge.write_rom(0x2000, 0x00);
+        assert_eq!(cartridge.read_rom(0x4000), 0x00);
+        cartridge.write_rom(0x2000, 0x23);
+        cartridge.write_rom(0x3000, 0x01);
+        assert_eq!(cartridge.read_rom(0x4000), 0x23);
+        assert_eq!(cartridge.read_rom(0x4001), 0x01);
+        // Rumble cartridges use bit 3 of the RAM Bank Number for the motor
+        cartridge.write_rom(0x4000, 0x0F);
+        assert!(cartridge.rumble_active());
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x42);
+        cartridge.write_rom(0x4000, 0x07);
+        assert_eq!(cartridge.read_ram(0xA000), 0x42);
+        assert!(!cartridge.rumble_active());
+    }
+}
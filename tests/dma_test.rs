@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::memory::Memory;
+    use tests::oh_boy::hardware::memory_map;
+    use tests::oh_boy::hardware::cartridge::RomOnly;
+
+    fn memory() -> Memory {
+        let mut rom: Vec<u8> = vec![0; 0x8000];
+        for i in 0..0xA0 {
+            rom[0x4000 + i] = 0x40 | i as u8;
+        }
+        let mut memory = Memory::from_cartridge(Box::new(RomOnly::new(rom, 0)));
+        for i in 0..0xA0 {
+            memory.write(memory_map::WR0 + i, 0x80 | i as u8);
+            memory.write(memory_map::WR0 + 0x100 + i, 0xFF - i as u8);
+        }
+        memory.write(memory_map::HRAM + 0x80, 0x12);
+        memory
+    }
+
+    fn oam(memory: &mut Memory) -> Vec<u8> {
+        (0..0xA0).map(|i| memory.fetch(memory_map::OAM0 + i)).collect()
+    }
+
+    #[test]
+    fn test_transfer_timing() {
+        let mut memory = memory();
+        memory.cpu_write(memory_map::DMA, 0xC0);
+        assert_eq!(memory.fetch(memory_map::DMA), 0xC0);
+
+        // A cycle to start, then a byte per cycle
+        memory.tick(1);
+        assert!(memory.dma_active());
+        assert_eq!(memory.fetch(memory_map::OAM0), 0x00);
+        memory.tick(1);
+        assert_eq!(memory.fetch(memory_map::OAM0), 0x80);
+        assert_eq!(memory.fetch(memory_map::OAM0 + 1), 0x00);
+        memory.tick(1);
+        assert_eq!(memory.fetch(memory_map::OAM0 + 1), 0x81);
+
+        // Only the I/O registers and HRAM are accessible meanwhile
+        assert_eq!(memory.cpu_fetch(memory_map::WR0 + 1), 0xFF);
+        assert_eq!(memory.cpu_fetch(0x0000), 0xFF);
+        memory.cpu_write(memory_map::WR0, 0x55);
+        assert_eq!(memory.cpu_fetch(memory_map::HRAM + 0x80), 0x12);
+
+        memory.tick(157);
+        assert!(memory.dma_active());
+        memory.tick(1);
+        assert!(!memory.dma_active());
+        assert_eq!(oam(&mut memory), (0..0xA0).map(|i| 0x80 | i as u8).collect::<Vec<u8>>());
+        // The write during the transfer was ignored
+        assert_eq!(memory.cpu_fetch(memory_map::WR0), 0x80);
+    }
+
+    #[test]
+    fn test_transfer_from_the_cartridge() {
+        let mut memory = memory();
+        memory.cpu_write(memory_map::DMA, 0x40);
+        memory.tick(1 + 0xA0);
+        assert_eq!(oam(&mut memory), (0..0xA0).map(|i| 0x40 | i as u8).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut memory = memory();
+        memory.cpu_write(memory_map::DMA, 0xC0);
+        memory.tick(1 + 10);
+
+        // The running transfer copies another byte before the new one takes over
+        memory.cpu_write(memory_map::DMA, 0xC1);
+        memory.tick(1);
+        assert_eq!(memory.fetch(memory_map::OAM0 + 10), 0x8A);
+        assert!(memory.dma_active());
+        memory.tick(0xA0);
+        assert!(!memory.dma_active());
+        assert_eq!(oam(&mut memory), (0..0xA0).map(|i| 0xFF - i as u8).collect::<Vec<u8>>());
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::gbs::*;
+    use tests::oh_boy::hardware::cartridge::Cartridge;
+
+    /// INIT stores the song number at C000; PLAY increments C001
+    fn gbs_file(songs: u8, timer_modulo: u8, timer_control: u8) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; 0x70];
+        data[0..3].copy_from_slice(b"GBS");
+        data[0x03] = 1;
+        data[0x04] = songs;
+        data[0x05] = 2;
+        data[0x06..0x08].copy_from_slice(&[0x00, 0x04]); // Load
+        data[0x08..0x0A].copy_from_slice(&[0x00, 0x04]); // INIT
+        data[0x0A..0x0C].copy_from_slice(&[0x04, 0x04]); // PLAY
+        data[0x0C..0x0E].copy_from_slice(&[0xFE, 0xDF]); // SP
+        data[0x0E] = timer_modulo;
+        data[0x0F] = timer_control;
+        data[0x10..0x14].copy_from_slice(b"Test");
+        data.extend_from_slice(&[
+            0xEA, 0x00, 0xC0, // LD (C000),A
+            0xC9,             // RET
+            0x21, 0x01, 0xC0, // LD HL,C001
+            0x34,             // INC (HL)
+            0xC9,             // RET
+        ]);
+        data
+    }
+
+    #[test]
+    fn test_header() {
+        let header = GbsHeader::parse(&gbs_file(3, 0x00, 0x00)).unwrap();
+        assert_eq!(header.songs, 3);
+        assert_eq!(header.first_song, 2);
+        assert_eq!(header.load_address, 0x0400);
+        assert_eq!(header.play_address, 0x0404);
+        assert_eq!(header.title, "Test");
+        assert!(!header.uses_timer());
+
+        assert!(GbsHeader::parse(&[0; 0x20]).is_err());
+        let mut data = gbs_file(3, 0x00, 0x00);
+        data[0] = b'X';
+        assert!(GbsHeader::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_rom_banking() {
+        let header = GbsHeader::parse(&gbs_file(1, 0x00, 0x00)).unwrap();
+        // Code loaded at 0400, up to the start of the third bank (the rest is padding)
+        let mut code: Vec<u8> = vec![0x00; 0x8001 - 0x0400];
+        code[0x4000 - 0x0400] = 0x11;
+        code[0x8000 - 0x0400] = 0x22;
+        let mut cartridge = GbsCartridge::new(&header, &code);
+        assert_eq!(cartridge.read_rom(0x4000), 0x11);
+        cartridge.write_rom(0x2000, 0x02);
+        assert_eq!(cartridge.read_rom(0x4000), 0x22);
+        assert_eq!(cartridge.read_rom(0x4001), 0xFF);
+
+        // Banks past the end of the ROM wrap around
+        cartridge.write_rom(0x2000, 0x03);
+        assert_eq!(cartridge.read_rom(0x4000), 0xC3);
+        cartridge.write_rom(0x2000, 0x04);
+        assert_eq!(cartridge.read_rom(0x4000), 0x11);
+        cartridge.write_rom(0x2000, 0xFF);
+        assert_eq!(cartridge.read_rom(0x4000), cartridge.read_rom(0x0000));
+    }
+
+    #[test]
+    fn test_init_and_play_at_vblank_rate() {
+        let mut player = GbsPlayer::new(gbs_file(3, 0x00, 0x00)).unwrap();
+        // The first song is 1-based in the header
+        assert_eq!(player.song(), 1);
+        assert_eq!(player.fetch(0xC000), 1);
+        assert_eq!(player.fetch(0xC001), 0);
+
+        for _ in 0..10 {
+            player.run_frame();
+        }
+        assert_eq!(player.fetch(0xC001), 10);
+
+        player.next();
+        player.next();
+        assert_eq!(player.song(), 0);
+        assert_eq!(player.fetch(0xC000), 0);
+        assert_eq!(player.fetch(0xC001), 0);
+        player.previous();
+        assert_eq!(player.song(), 2);
+    }
+
+    #[test]
+    fn test_play_at_timer_rate() {
+        // 4096 Hz div (256 - 0xC0): 64 Hz, so about 1.07 calls per frame
+        let mut player = GbsPlayer::new(gbs_file(1, 0xC0, 0x04)).unwrap();
+        for _ in 0..60 {
+            player.run_frame();
+        }
+        assert_eq!(player.fetch(0xC001), 64);
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::emulator::info;
+    use tests::oh_boy::hardware::cartridge_header::CartridgeHeader;
+
+    use std::fs::File;
+    use std::io::prelude::*;
+
+    fn nintendo_logo_rom() -> Vec<u8> {
+        let mut rom: Vec<u8> = Vec::new();
+        File::open("./data/nintendo-logo.gb").unwrap().read_to_end(&mut rom).unwrap();
+        rom
+    }
+
+    #[test]
+    fn test_describe() {
+        let rom = nintendo_logo_rom();
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        let expected = concat!(
+            "File            : nintendo-logo.gb (32768 bytes)\n",
+            "Title           : \n",
+            "Licensee        : None (old code 0x00)\n",
+            "Destination     : Overseas\n",
+            "Version         : 0x00\n",
+            "CGB             : No\n",
+            "SGB             : No\n",
+            "Cartridge Type  : 0x00 ROM ONLY\n",
+            "Supported       : Yes\n",
+            "ROM Size        : 0x00 32 kB (2 banks)\n",
+            "RAM Size        : 0x00 0 kB\n",
+            "Nintendo Logo   : OK\n",
+            "Header Checksum : stored 0xE6, computed 0xE6 OK\n",
+            "Global Checksum : stored 0x31BB, computed 0x31BB OK\n",
+        );
+        assert_eq!(info::describe("nintendo-logo.gb", &rom, &header), expected);
+    }
+
+    #[test]
+    fn test_describe_mismatches() {
+        let mut rom = nintendo_logo_rom();
+        rom[0x0104] ^= 0xFF;
+        rom[0x0134] = b'X';
+        rom.truncate(0x4000);
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        let report = info::describe("nintendo-logo.gb", &rom, &header);
+        assert!(report.contains("Title           : X\n"));
+        assert!(report.contains("                  file size does not match the ROM size at the header\n"));
+        assert!(report.contains("Nintendo Logo   : MISMATCH\n"));
+        assert!(report.contains("Header Checksum : stored 0xE6, computed 0x8E MISMATCH\n"));
+    }
+
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    extern crate sdl2;
+    use tests::oh_boy::emulator::input::*;
+    use tests::oh_boy::hardware::joypad::Button;
+    use tests::sdl2::keyboard::Keycode;
+    use tests::sdl2::controller::{Axis, Button as ControllerButton};
+
+    #[test]
+    fn test_default_bindings_file() {
+        let bindings = InputBindings::from_file("./data/bindings.cfg").unwrap();
+        assert_eq!(bindings.keyboard_button(Keycode::Right), Some(Button::Right));
+        assert_eq!(bindings.keyboard_button(Keycode::D), Some(Button::Right));
+        assert_eq!(bindings.keyboard_button(Keycode::K), Some(Button::A));
+        assert_eq!(bindings.keyboard_button(Keycode::Return), Some(Button::Start));
+        assert_eq!(bindings.keyboard_button(Keycode::Space), None);
+        assert_eq!(bindings.controller_button(ControllerButton::B), Some(Button::A));
+        assert_eq!(bindings.controller_button(ControllerButton::A), Some(Button::B));
+        assert_eq!(bindings.controller_button(ControllerButton::Back), Some(Button::Select));
+        assert_eq!(bindings.deadzone(), 16_000);
+    }
+
+    #[test]
+    fn test_rebinding() {
+        let bindings = InputBindings::parse("keyboard.a = Space, Left Shift\ncontroller.start =\n").unwrap();
+        assert_eq!(bindings.keyboard_button(Keycode::Space), Some(Button::A));
+        assert_eq!(bindings.keyboard_button(Keycode::LShift), Some(Button::A));
+        // The previous binding of the button is replaced; the other buttons keep theirs
+        assert_eq!(bindings.keyboard_button(Keycode::X), None);
+        assert_eq!(bindings.keyboard_button(Keycode::Z), Some(Button::B));
+        assert_eq!(bindings.controller_button(ControllerButton::Start), None);
+    }
+
+    #[test]
+    fn test_controller_input_names_in_any_case() {
+        let bindings = InputBindings::parse("controller.a = B, LeftX+\ncontroller.start = START").unwrap();
+        assert_eq!(bindings.controller_button(ControllerButton::B), Some(Button::A));
+        assert!(bindings.controller_axis(Axis::LeftX, 32_767).contains(
+            &(ControllerInput::Axis(Axis::LeftX, true), Button::A, true)));
+        assert_eq!(bindings.controller_button(ControllerButton::Start), Some(Button::Start));
+        assert_eq!(InputBindings::parse("controller.a = LeftZ+").err().unwrap(),
+            "line 1: unknown controller input `LeftZ+`");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |contents: &str| InputBindings::parse(contents).err().unwrap();
+        assert_eq!(error("# comment\n\nkeyboard.a X"), "line 3: expected `<device>.<button> = <inputs>`");
+        assert_eq!(error("keyboard.turbo = X"), "line 1: unknown GAME BOY button `keyboard.turbo`");
+        assert_eq!(error("mouse.a = X"), "line 1: unknown device `mouse` (keyboard or controller)");
+        assert_eq!(error("keyboard.a = NoSuchKey"), "line 1: unknown key `NoSuchKey`");
+        assert_eq!(error("controller.a = leftz+"), "line 1: unknown controller input `leftz+`");
+    }
+
+    #[test]
+    fn test_deadzone_bounds() {
+        assert_eq!(InputBindings::parse("controller.deadzone = 0").unwrap().deadzone(), 0);
+        assert_eq!(InputBindings::parse("controller.deadzone = 32767").unwrap().deadzone(), 32_767);
+        assert_eq!(InputBindings::parse("controller.deadzone = 32768").err().unwrap(),
+            "line 1: invalid deadzone `32768` (0 to 32767)");
+        assert_eq!(InputBindings::parse("controller.deadzone = -1").err().unwrap(),
+            "line 1: invalid deadzone `-1` (0 to 32767)");
+        assert!(InputBindings::parse("controller.deadzone = -32768").is_err());
+
+        // A centred stick is never held, and a full tilt always is (up to the widest deadzone)
+        let bindings = InputBindings::parse("controller.deadzone = 0").unwrap();
+        assert!(bindings.controller_axis(Axis::LeftX, 0).iter().all(|&(_, _, held)| !held));
+        let bindings = InputBindings::parse("controller.deadzone = 32767").unwrap();
+        assert!(bindings.controller_axis(Axis::LeftX, 32_767).iter().all(|&(_, _, held)| !held));
+        assert!(bindings.controller_axis(Axis::LeftX, -32_768).contains(
+            &(ControllerInput::Axis(Axis::LeftX, false), Button::Left, true)));
+    }
+
+    #[test]
+    fn test_axis_edges() {
+        let bindings = InputBindings::new();
+        let mut held_buttons = HeldButtons::new();
+        let mut edges: Vec<(Button, bool)> = Vec::new();
+        for &value in [0, 10_000, 20_000, 30_000, 10_000, -20_000, -30_000, 0].iter() {
+            for (input, button, held) in bindings.controller_axis(Axis::LeftX, value) {
+                let input = HostInput::Controller(0, input);
+                match held {
+                    true  => if held_buttons.press(input, button) { edges.push((button, true)); },
+                    false => if let Some(button) = held_buttons.release(input) { edges.push((button, false)); },
+                }
+            }
+        }
+        assert_eq!(edges, vec![
+            (Button::Right, true), (Button::Right, false),
+            (Button::Left, true), (Button::Left, false),
+        ]);
+        assert!(bindings.controller_axis(Axis::TriggerLeft, 30_000).is_empty());
+    }
+
+    #[test]
+    fn test_held_buttons() {
+        let mut held_buttons = HeldButtons::new();
+        let key = HostInput::Key(Keycode::X);
+        let pad = HostInput::Controller(1, ControllerInput::Button(ControllerButton::B));
+        let stick = HostInput::Controller(1, ControllerInput::Axis(Axis::LeftX, true));
+        let other_pad = HostInput::Controller(2, ControllerInput::Button(ControllerButton::B));
+
+        // The button is released along with the last input holding it
+        assert!(held_buttons.press(key, Button::A));
+        assert!(!held_buttons.press(pad, Button::A));
+        assert_eq!(held_buttons.release(key), None);
+        assert!(held_buttons.is_held(Button::A));
+        assert_eq!(held_buttons.release(pad), Some(Button::A));
+        assert_eq!(held_buttons.release(pad), None);
+
+        // Unplugging a controller releases what it was holding, but not the other inputs
+        assert!(held_buttons.press(pad, Button::A));
+        assert!(held_buttons.press(stick, Button::Right));
+        assert!(!held_buttons.press(other_pad, Button::A));
+        let mut released = held_buttons.release_controller(1);
+        assert_eq!(released, vec![Button::Right]);
+        assert!(held_buttons.is_held(Button::A));
+        assert!(held_buttons.press(key, Button::Start));
+        released = held_buttons.release_controller(2);
+        assert_eq!(released, vec![Button::A]);
+        assert!(!held_buttons.is_held(Button::A));
+        assert!(held_buttons.is_held(Button::Start));
+    }
+
+}
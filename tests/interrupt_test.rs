@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::interrupt;
+    use tests::oh_boy::hardware::cpu::CPU;
+    use tests::oh_boy::hardware::memory::Memory;
+    use tests::oh_boy::hardware::memory_map;
+    use tests::oh_boy::hardware::cartridge::RomOnly;
+
+    fn setup(interrupt_enable: u8, interrupt_request: u8) -> (CPU, Memory) {
+        let mut memory = Memory::from_cartridge(Box::new(RomOnly::new(vec![0; 0x8000], 0)));
+        memory.write(memory_map::IE, interrupt_enable);
+        memory.write(memory_map::IF, interrupt_request);
+        let mut cpu = CPU::new();
+        cpu.boot_expected();
+        cpu.interruption_enabled = true;
+        (cpu, memory)
+    }
+
+    #[test]
+    fn test_dispatch_by_priority() {
+        let (mut cpu, mut memory) = setup(0x1F, 0x06);
+        assert_eq!(cpu.cycle(&mut memory), 5);
+        assert_eq!(cpu.registers.program_counter, 0x0048);
+        assert_eq!(cpu.registers.stack_pointer, 0xFFFC);
+        assert_eq!(memory.fetch(0xFFFD), 0x01);
+        assert_eq!(memory.fetch(0xFFFC), 0x00);
+        assert_eq!(memory.fetch(memory_map::IF), 0xE4);
+        assert!(!cpu.interruption_enabled);
+    }
+
+    #[test]
+    fn test_only_enabled_interrupts() {
+        // VBLANK is requested, but only the TIMER is enabled
+        let (mut cpu, mut memory) = setup(0x04, 0x01);
+        assert_eq!(interrupt::handler(&mut cpu, &mut memory), 0);
+        assert_eq!(cpu.registers.program_counter, 0x0100);
+        assert_eq!(memory.fetch(memory_map::IF), 0xE1);
+
+        let (mut cpu, mut memory) = setup(0x1F, 0x00);
+        assert_eq!(interrupt::handler(&mut cpu, &mut memory), 0);
+        assert_eq!(cpu.registers.program_counter, 0x0100);
+    }
+
+    #[test]
+    fn test_halt_wakes_without_ime() {
+        let (mut cpu, mut memory) = setup(0x04, 0x00);
+        cpu.interruption_enabled = false;
+        cpu.halted = true;
+        assert_eq!(cpu.cycle(&mut memory), 1);
+        assert!(cpu.halted);
+
+        // The interrupt is left pending, and the execution goes on after the HALT
+        memory.write(memory_map::IF, 0x04);
+        assert_eq!(cpu.cycle(&mut memory), 1);
+        assert!(!cpu.halted);
+        assert_eq!(cpu.registers.program_counter, 0x0101);
+        assert_eq!(memory.fetch(memory_map::IF), 0xE4);
+    }
+}
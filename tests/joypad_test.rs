@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::joypad::*;
+
+    #[test]
+    fn test_select_lines() {
+        let mut joypad = Joypad::new();
+        assert_eq!(joypad.read(), 0xFF);
+
+        joypad.press(Button::Start);
+        joypad.press(Button::Left);
+        assert_eq!(joypad.read(), 0xFF);
+
+        // P14 low: direction keys
+        joypad.write(0x20);
+        assert_eq!(joypad.read(), 0xED);
+        // P15 low: action buttons
+        joypad.write(0x10);
+        assert_eq!(joypad.read(), 0xD7);
+        // Both groups selected
+        joypad.write(0x00);
+        assert_eq!(joypad.read(), 0xC5);
+
+        joypad.release(Button::Left);
+        assert_eq!(joypad.read(), 0xC7);
+    }
+
+    #[test]
+    fn test_interrupt_on_falling_edge() {
+        let mut joypad = Joypad::new();
+        // Pressing a button of an unselected group doesn't change the read lines
+        assert!(!joypad.press(Button::A));
+        // Selecting the group drives P10 low
+        assert!(joypad.write(0x10));
+        assert!(!joypad.write(0x10));
+        assert!(!joypad.press(Button::Up));
+        assert!(joypad.press(Button::B));
+        joypad.release(Button::B);
+        assert!(!joypad.write(0x30));
+    }
+}
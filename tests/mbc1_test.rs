@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::mbc1::*;
+    use tests::oh_boy::hardware::cartridge::{Cartridge, ROM_BANK_SIZE, RAM_BANK_SIZE};
+    use tests::oh_boy::hardware::memory_map;
+
+    /// Builds a ROM where the first byte of every bank holds its number
+    fn rom(banks: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; banks * ROM_BANK_SIZE];
+        for bank in 0..banks {
+            data[bank * ROM_BANK_SIZE] = bank as u8;
+        }
+        data
+    }
+
+    /// A 1MB MBC1M multicart: every game starts with the Nintendo logo
+    fn multicart_rom() -> Vec<u8> {
+        let mut data: Vec<u8> = rom(64);
+        for game in 0..4 {
+            for address in memory_map::NTD0..=memory_map::NTD9 {
+                data[game * 0x10 * ROM_BANK_SIZE + address as usize] = address as u8;
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_bank_0_is_read_as_bank_1() {
+        let mut mbc1 = MBC1::new(rom(4), 0);
+        assert_eq!(mbc1.read_rom(0x4000), 0x01);
+        mbc1.write_rom(0x2000, 0x02);
+        assert_eq!(mbc1.read_rom(0x4000), 0x02);
+        mbc1.write_rom(0x2000, 0x00);
+        assert_eq!(mbc1.upper_rom_bank(), 0x01);
+        assert_eq!(mbc1.read_rom(0x4000), 0x01);
+        // Only the lower 5 bits are used: 0x20 is 0x00, read as 0x01
+        mbc1.write_rom(0x3FFF, 0x20);
+        assert_eq!(mbc1.read_rom(0x4000), 0x01);
+        assert_eq!(mbc1.read_rom(0x0000), 0x00);
+    }
+
+    #[test]
+    fn test_banks_0x20_0x40_0x60() {
+        let mut mbc1 = MBC1::new(rom(128), 0);
+        for &(bank2, bank) in [(0x01, 0x21), (0x02, 0x41), (0x03, 0x61)].iter() {
+            mbc1.write_rom(0x2000, 0x00);
+            mbc1.write_rom(0x4000, bank2);
+            assert_eq!(mbc1.read_rom(0x4000), bank);
+            mbc1.write_rom(0x2000, 0x05);
+            assert_eq!(mbc1.read_rom(0x4000), bank + 0x04);
+        }
+        // Those banks are only reachable at 0000-3FFF, in the advanced banking mode
+        assert_eq!(mbc1.lower_rom_bank(), 0x00);
+        mbc1.write_rom(0x6000, 0x01);
+        assert_eq!(mbc1.read_rom(0x0000), 0x60);
+        mbc1.write_rom(0x4000, 0x01);
+        assert_eq!(mbc1.read_rom(0x0000), 0x20);
+        mbc1.write_rom(0x6000, 0x00);
+        assert_eq!(mbc1.read_rom(0x0000), 0x00);
+    }
+
+    #[test]
+    fn test_ram_enable() {
+        let mut mbc1 = MBC1::new(rom(4), RAM_BANK_SIZE);
+        mbc1.write_ram(0xA000, 0x42);
+        assert_eq!(mbc1.read_ram(0xA000), 0xFF);
+        // Only 0x0A in the lower nibble enables the RAM
+        mbc1.write_rom(0x1FFF, 0x1A);
+        mbc1.write_ram(0xA000, 0x42);
+        assert_eq!(mbc1.read_ram(0xA000), 0x42);
+        mbc1.write_rom(0x0000, 0x0B);
+        assert_eq!(mbc1.read_ram(0xA000), 0xFF);
+        mbc1.write_rom(0x0000, 0x0A);
+        assert_eq!(mbc1.read_ram(0xBFFF), 0x00);
+        assert_eq!(mbc1.read_ram(0xA000), 0x42);
+    }
+
+    #[test]
+    fn test_ram_banking_modes() {
+        let mut mbc1 = MBC1::new(rom(4), 4 * RAM_BANK_SIZE);
+        mbc1.write_rom(0x0000, 0x0A);
+        for bank in 0..4 {
+            mbc1.write_rom(0x6000, 0x01);
+            mbc1.write_rom(0x4000, bank);
+            mbc1.write_ram(0xA000, 0x10 | bank);
+        }
+
+        // The simple banking mode always maps RAM bank #0
+        mbc1.write_rom(0x6000, 0x00);
+        for bank in 0..4 {
+            mbc1.write_rom(0x4000, bank);
+            assert_eq!(mbc1.ram_bank(), 0);
+            assert_eq!(mbc1.read_ram(0xA000), 0x10);
+        }
+
+        // The advanced banking mode maps BANK2
+        mbc1.write_rom(0x6000, 0x01);
+        for bank in 0..4 {
+            mbc1.write_rom(0x4000, bank);
+            assert_eq!(mbc1.ram_bank(), bank as usize);
+            assert_eq!(mbc1.read_ram(0xA000), 0x10 | bank);
+        }
+        assert_eq!(mbc1.save_data().len(), 4 * RAM_BANK_SIZE);
+    }
+
+    #[test]
+    fn test_multicart_wiring() {
+        let mut mbc1 = MBC1::new(multicart_rom(), 0);
+        // BANK1 uses 4 bits, and BANK2 selects the game (0x10 banks each)
+        mbc1.write_rom(0x2000, 0x12);
+        assert_eq!(mbc1.read_rom(0x4000), 0x02);
+        mbc1.write_rom(0x4000, 0x01);
+        assert_eq!(mbc1.upper_rom_bank(), 0x12);
+        assert_eq!(mbc1.read_rom(0x4000), 0x12);
+        mbc1.write_rom(0x4000, 0x03);
+        assert_eq!(mbc1.read_rom(0x4000), 0x32);
+        // The advanced banking mode maps the first bank of the game at 0000-3FFF
+        mbc1.write_rom(0x6000, 0x01);
+        assert_eq!(mbc1.read_rom(0x0000), 0x30);
+
+        // The same ROM size with a single logo is a regular MBC1 cartridge
+        let mut data: Vec<u8> = rom(64);
+        for address in memory_map::NTD0..=memory_map::NTD9 {
+            data[address as usize] = address as u8;
+        }
+        let mut mbc1 = MBC1::new(data, 0);
+        mbc1.write_rom(0x2000, 0x12);
+        mbc1.write_rom(0x4000, 0x01);
+        assert_eq!(mbc1.read_rom(0x4000), 0x32);
+    }
+}
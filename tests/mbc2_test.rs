@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::mbc2::*;
+    use tests::oh_boy::hardware::cartridge::{Cartridge, ROM_BANK_SIZE};
+
+    /// Builds a ROM where the first byte of every bank holds its number
+    fn rom(banks: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; banks * ROM_BANK_SIZE];
+        for bank in 0..banks {
+            data[bank * ROM_BANK_SIZE] = bank as u8;
+        }
+        data
+    }
+
+    #[test]
+    fn test_register_selection() {
+        let mut mbc2 = MBC2::new(rom(16));
+        // Address bit 8 set: ROM Bank Number (anywhere in 0000-3FFF)
+        mbc2.write_rom(0x0100, 0x03);
+        assert_eq!(mbc2.read_rom(0x4000), 0x03);
+        mbc2.write_rom(0x3FFF, 0x1F);
+        assert_eq!(mbc2.read_rom(0x4000), 0x0F);
+        mbc2.write_rom(0x2100, 0x00);
+        assert_eq!(mbc2.read_rom(0x4000), 0x01);
+        // Address bit 8 clear: RAM Enable, which leaves the ROM bank alone
+        mbc2.write_rom(0x2000, 0x0A);
+        assert_eq!(mbc2.read_rom(0x4000), 0x01);
+        mbc2.write_ram(0xA000, 0x05);
+        assert_eq!(mbc2.read_ram(0xA000), 0xF5);
+        mbc2.write_rom(0x3EFF, 0x00);
+        assert_eq!(mbc2.read_ram(0xA000), 0xFF);
+        // Writes beyond the ROM area are ignored
+        mbc2.write_rom(0x4100, 0x02);
+        assert_eq!(mbc2.read_rom(0x4000), 0x01);
+    }
+
+    #[test]
+    fn test_half_byte_ram() {
+        let mut mbc2 = MBC2::new(rom(2));
+        mbc2.write_rom(0x0000, 0x0A);
+        for offset in 0..0x200 {
+            mbc2.write_ram(0xA000 + offset, offset as u8);
+        }
+        for offset in 0..0x200 {
+            assert_eq!(mbc2.read_ram(0xA000 + offset), 0xF0 | (offset as u8 & 0x0F));
+        }
+        // Only the lower nibbles are kept
+        assert_eq!(mbc2.save_data().len(), 0x200);
+        assert!(mbc2.save_data().iter().all(|&word| word <= 0x0F));
+    }
+
+    #[test]
+    fn test_ram_echo() {
+        let mut mbc2 = MBC2::new(rom(2));
+        mbc2.write_rom(0x0000, 0x0A);
+        mbc2.write_ram(0xA123, 0x07);
+        for echo in 0..16 {
+            assert_eq!(mbc2.read_ram(0xA123 + echo * 0x200), 0xF7);
+        }
+        // Writes through the echo reach the same half-byte
+        mbc2.write_ram(0xBF23, 0x0C);
+        assert_eq!(mbc2.read_ram(0xA123), 0xFC);
+    }
+}
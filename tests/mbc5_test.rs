@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::mbc5::*;
+    use tests::oh_boy::hardware::cartridge::{Cartridge, ROM_BANK_SIZE, RAM_BANK_SIZE};
+
+    /// Builds a ROM where the first two bytes of every bank hold its number
+    fn rom(banks: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; banks * ROM_BANK_SIZE];
+        for bank in 0..banks {
+            data[bank * ROM_BANK_SIZE] = bank as u8;
+            data[bank * ROM_BANK_SIZE + 1] = (bank >> 8) as u8;
+        }
+        data
+    }
+
+    fn rom_bank(mbc5: &MBC5) -> usize {
+        mbc5.read_rom(0x4000) as usize | (mbc5.read_rom(0x4001) as usize) << 8
+    }
+
+    #[test]
+    fn test_nine_bit_rom_bank() {
+        let mut mbc5 = MBC5::new(rom(512), 0, false);
+        assert_eq!(rom_bank(&mbc5), 0x001);
+        mbc5.write_rom(0x2000, 0xFF);
+        assert_eq!(rom_bank(&mbc5), 0x0FF);
+        // The 9th bit (0x3000-0x3FFF) keeps the lower 8 bits
+        mbc5.write_rom(0x3000, 0x01);
+        assert_eq!(rom_bank(&mbc5), 0x1FF);
+        mbc5.write_rom(0x2FFF, 0x23);
+        assert_eq!(rom_bank(&mbc5), 0x123);
+        // Only bit 0 is used
+        mbc5.write_rom(0x3FFF, 0xFE);
+        assert_eq!(rom_bank(&mbc5), 0x023);
+        assert_eq!(mbc5.read_rom(0x0000), 0x00);
+    }
+
+    #[test]
+    fn test_bank_0_at_4000() {
+        let mut mbc5 = MBC5::new(rom(4), 0, false);
+        mbc5.write_rom(0x2000, 0x00);
+        assert_eq!(rom_bank(&mbc5), 0x000);
+        mbc5.write_rom(0x2000, 0x03);
+        assert_eq!(rom_bank(&mbc5), 0x003);
+    }
+
+    #[test]
+    fn test_sixteen_ram_banks() {
+        let mut mbc5 = MBC5::new(rom(4), 16 * RAM_BANK_SIZE, false);
+        mbc5.write_ram(0xA000, 0x42);
+        assert_eq!(mbc5.read_ram(0xA000), 0xFF);
+        mbc5.write_rom(0x0000, 0x0A);
+        for bank in 0..16 {
+            mbc5.write_rom(0x4000, bank);
+            mbc5.write_ram(0xBFFF, 0x80 | bank);
+        }
+        for bank in 0..16 {
+            mbc5.write_rom(0x4000, bank);
+            assert_eq!(mbc5.read_ram(0xBFFF), 0x80 | bank);
+        }
+        // The upper bits of the RAM Bank Number are ignored
+        mbc5.write_rom(0x4000, 0xF3);
+        assert_eq!(mbc5.read_ram(0xBFFF), 0x83);
+        assert!(!mbc5.rumble_active());
+    }
+
+    #[test]
+    fn test_rumble_masking() {
+        let mut mbc5 = MBC5::new(rom(4), 8 * RAM_BANK_SIZE, true);
+        mbc5.write_rom(0x0000, 0x0A);
+        mbc5.write_rom(0x4000, 0x05);
+        mbc5.write_ram(0xA000, 0x55);
+        assert!(!mbc5.rumble_active());
+
+        // Bit 3 drives the motor instead of selecting a RAM bank
+        mbc5.write_rom(0x4000, 0x0D);
+        assert!(mbc5.rumble_active());
+        assert_eq!(mbc5.read_ram(0xA000), 0x55);
+        mbc5.write_ram(0xA000, 0x66);
+        mbc5.write_rom(0x4000, 0x05);
+        assert!(!mbc5.rumble_active());
+        assert_eq!(mbc5.read_ram(0xA000), 0x66);
+        mbc5.write_rom(0x4000, 0x00);
+        assert_eq!(mbc5.read_ram(0xA000), 0x00);
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::pixel_fifo::*;
+    use tests::oh_boy::hardware::video::{Video, FRAME_WIDTH};
+    use tests::oh_boy::hardware::memory::Memory;
+    use tests::oh_boy::hardware::memory_map;
+    use tests::oh_boy::hardware::cartridge::RomOnly;
+
+    /// BG: tile 0 (color 1) on even columns and tile 1 (color 2) on odd ones; window: tile 2
+    /// (color 3); objects: tile 3 (color 3 on its left half)
+    fn memory() -> Memory {
+        let mut memory = Memory::from_cartridge(Box::new(RomOnly::new(vec![0; 0x8000], 0)));
+        for row in 0..8 {
+            let tiles: [(u8, u8); 4] = [(0xFF, 0x00), (0x00, 0xFF), (0xFF, 0xFF), (0xF0, 0xF0)];
+            for (character_code, &(lsb, msb)) in tiles.iter().enumerate() {
+                memory.write(0x8000 + character_code as u16 * 16 + row * 2, lsb);
+                memory.write(0x8000 + character_code as u16 * 16 + row * 2 + 1, msb);
+            }
+        }
+        for block in 0..0x400 {
+            memory.write(0x9800 + block, (block % 2) as u8);
+            memory.write(0x9C00 + block, 0x02);
+        }
+        memory.write(memory_map::LCDC, 0xF3);
+        memory.write(memory_map::LY, 0x00);
+        memory.write(memory_map::BGP, 0xE4);
+        memory.write(memory_map::OBP0, 0xE4);
+        memory.write(memory_map::WX, 0xFF);
+        memory
+    }
+
+    fn object(memory: &mut Memory, index: u16, x: u8) {
+        let address = memory_map::OAM0 + index * 4;
+        memory.write(address, 16);
+        memory.write(address + 1, x);
+        memory.write(address + 2, 0x03);
+        memory.write(address + 3, 0x00);
+    }
+
+    /// Returns the length of mode 3, in dots
+    fn render_line(memory: &mut Memory, video: &mut Video) -> usize {
+        let mut pixel_fifo = PixelFifo::new();
+        video.search_oam(memory);
+        pixel_fifo.start_line(memory, video);
+        assert!(pixel_fifo.advance(memory, video, 1000));
+        pixel_fifo.mode3_dots()
+    }
+
+    #[test]
+    fn test_mode3_length() {
+        let mut memory = memory();
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 172);
+
+        // The SCX fine scroll drops pixels at the start of the line
+        memory.write(memory_map::SCX, 0x03);
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 175);
+        memory.write(memory_map::SCX, 0x00);
+
+        // Each object stalls the output: 6 dots, plus up to 5 waiting for the fetcher on the
+        // first object of a tile (the closer to the left edge of the tile, the longer)
+        object(&mut memory, 0, 40);
+        object(&mut memory, 1, 80);
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 172 + 11 + 11);
+        object(&mut memory, 1, 42);
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 172 + 11 + 6);
+        object(&mut memory, 1, 0);
+        object(&mut memory, 0, 45);
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 172 + 6);
+        memory.write(memory_map::SCX, 0x03);
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 175 + 11);
+        object(&mut memory, 0, 43);
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 175 + 6);
+        memory.write(memory_map::SCX, 0x00);
+        object(&mut memory, 0, 0);
+
+        // The window restarts the fetcher
+        memory.write(memory_map::WX, 7 + 80);
+        assert_eq!(render_line(&mut memory, &mut Video::new()), 178);
+    }
+
+    #[test]
+    fn test_window_spanning_a_line() {
+        let mut memory = memory();
+        let mut video = Video::new();
+        let window_pixels = |video: &Video, ly: usize| {
+            video.frame_buffer[(ly * FRAME_WIDTH)..((ly + 1) * FRAME_WIDTH)].iter().filter(|&&color| color == 3).count()
+        };
+
+        // WX=166 shows a pixel of the window, and the window carries on over the next line
+        memory.write(memory_map::WY, 0x00);
+        memory.write(memory_map::WX, 166);
+        render_line(&mut memory, &mut video);
+        assert_eq!(window_pixels(&video, 0), 1);
+        memory.write(memory_map::LY, 0x01);
+        memory.write(memory_map::WX, 0xFF);
+        render_line(&mut memory, &mut video);
+        assert_eq!(window_pixels(&video, 1), FRAME_WIDTH);
+
+        // A line without the window ends it
+        memory.write(memory_map::WX, 166);
+        render_line(&mut memory, &mut video);
+        memory.write(memory_map::LY, 0x02);
+        memory.write(memory_map::LCDC, 0xD3);
+        render_line(&mut memory, &mut video);
+        assert_eq!(window_pixels(&video, 2), 0);
+        memory.write(memory_map::LY, 0x03);
+        memory.write(memory_map::LCDC, 0xF3);
+        memory.write(memory_map::WX, 0xFF);
+        render_line(&mut memory, &mut video);
+        assert_eq!(window_pixels(&video, 3), 0);
+    }
+
+    #[test]
+    fn test_matches_the_scanline_renderer() {
+        let mut memory = memory();
+        memory.write(memory_map::SCX, 0x05);
+        memory.write(memory_map::WX, 7 + 100);
+        object(&mut memory, 0, 20);
+        object(&mut memory, 1, 24);
+        object(&mut memory, 2, 4);
+
+        let mut video = Video::new();
+        render_line(&mut memory, &mut video);
+        let pixel_fifo_line = video.frame_buffer[0..FRAME_WIDTH].to_vec();
+
+        let mut video = Video::new();
+        video.search_oam(&mut memory);
+        video.update_scanline(&mut memory);
+        assert_eq!(&video.frame_buffer[0..FRAME_WIDTH], &pixel_fifo_line[..]);
+    }
+
+    #[test]
+    fn test_mid_line_palette_change() {
+        let mut memory = memory();
+        let mut video = Video::new();
+        let mut pixel_fifo = PixelFifo::new();
+
+        video.search_oam(&mut memory);
+        pixel_fifo.start_line(&mut memory, &mut video);
+        // 6 dots for the first fetch, then a pixel per dot
+        assert!(!pixel_fifo.advance(&mut memory, &mut video, 6 + 80));
+        memory.write(memory_map::BGP, 0x1B);
+        assert!(pixel_fifo.advance(&mut memory, &mut video, 1000));
+
+        assert_eq!(&video.frame_buffer[0..16], &[1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(&video.frame_buffer[80..96], &[2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1]);
+    }
+}
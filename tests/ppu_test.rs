@@ -0,0 +1,203 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::ppu::*;
+    use tests::oh_boy::hardware::memory::Memory;
+    use tests::oh_boy::hardware::memory_map;
+    use tests::oh_boy::hardware::video_mode;
+    use tests::oh_boy::hardware::cartridge::RomOnly;
+
+    const LCDC_INTERRUPT: u8 = 0x02;
+
+    fn memory(stat: u8, lyc: u8) -> Memory {
+        let mut memory = Memory::from_cartridge(Box::new(RomOnly::new(vec![0; 0x8000], 0)));
+        memory.write(memory_map::LCDC, 0x91);
+        memory.write(memory_map::STAT, stat);
+        memory.write(memory_map::LYC, lyc);
+        memory.write(memory_map::IF, 0x00);
+        memory
+    }
+
+    fn mode(memory: &mut Memory) -> u8 {
+        memory.fetch(memory_map::STAT) & 0x03
+    }
+
+    /// Runs the PPU until a frame starts (LY=0, after VBLANK)
+    fn next_frame(ppu: &mut PPU, memory: &mut Memory) {
+        loop {
+            let previous_mode: u8 = mode(memory);
+            ppu.cycle(memory, 1);
+            if previous_mode == video_mode::VBLANK && mode(memory) == video_mode::SEARCH_OAM {
+                break;
+            }
+        }
+    }
+
+    /// Counts the LCDC interrupts requested along a frame
+    fn frame_interrupts(memory: &mut Memory) -> usize {
+        let mut ppu = PPU::new();
+        let mut interrupts: usize = 0;
+        next_frame(&mut ppu, memory);
+        memory.write(memory_map::IF, 0x00);
+        loop {
+            let previous_mode: u8 = mode(memory);
+            ppu.cycle(memory, 1);
+            if memory.fetch(memory_map::IF) & LCDC_INTERRUPT != 0x00 {
+                interrupts += 1;
+                memory.write(memory_map::IF, 0x00);
+            }
+            if previous_mode == video_mode::VBLANK && mode(memory) == video_mode::SEARCH_OAM {
+                return interrupts;
+            }
+        }
+    }
+
+    #[test]
+    fn test_lyc_match_flag() {
+        let mut memory = memory(0x00, 0x02);
+        let mut ppu = PPU::new();
+        next_frame(&mut ppu, &mut memory);
+        while memory.fetch(memory_map::LY) != 0x02 {
+            assert_eq!(memory.fetch(memory_map::STAT) & 0x04, 0x00);
+            ppu.cycle(&mut memory, 1);
+        }
+        // The flag is set along the whole line, and cleared on the next one
+        while memory.fetch(memory_map::LY) == 0x02 {
+            assert_eq!(memory.fetch(memory_map::STAT) & 0x04, 0x04);
+            ppu.cycle(&mut memory, 1);
+        }
+        assert_eq!(memory.fetch(memory_map::STAT) & 0x04, 0x00);
+    }
+
+    #[test]
+    fn test_interrupt_on_rising_edge() {
+        // Once per frame for the LYC match, and once per line for HBLANK
+        assert_eq!(frame_interrupts(&mut memory(0x40, 0x02)), 1);
+        assert_eq!(frame_interrupts(&mut memory(0x08, 0x00)), 144);
+        // VBLANK lasts 10 lines, but the line only rises once
+        assert_eq!(frame_interrupts(&mut memory(0x10, 0x00)), 1);
+    }
+
+    #[test]
+    fn test_stat_blocking() {
+        // HBLANK holds the line high into the OAM search of the next line, so only the
+        // OAM search of the first line requests an interrupt
+        assert_eq!(frame_interrupts(&mut memory(0x28, 0xFF)), 144 + 1);
+        // The LYC match of line 5 holds the line high into the OAM search of line 6
+        assert_eq!(frame_interrupts(&mut memory(0x60, 0x05)), 144 - 1);
+    }
+
+    #[test]
+    fn test_stat_writable_bits() {
+        let mut memory = memory(0x00, 0x00);
+        memory.set_lcd_status(video_mode::SCANLINE, true);
+        assert_eq!(memory.fetch(memory_map::STAT), 0x87);
+        memory.write(memory_map::STAT, 0xF8);
+        assert_eq!(memory.fetch(memory_map::STAT), 0xFF);
+        memory.write(memory_map::STAT, 0x00);
+        assert_eq!(memory.fetch(memory_map::STAT), 0x87);
+    }
+
+    #[test]
+    fn test_vram_and_oam_access() {
+        let mut memory = memory(0x00, 0x00);
+        memory.write(0x8000, 0x12);
+        memory.write(memory_map::OAM0, 0x34);
+
+        memory.set_lcd_status(video_mode::SEARCH_OAM, false);
+        assert_eq!(memory.cpu_fetch(0x8000), 0x12);
+        assert_eq!(memory.cpu_fetch(memory_map::OAM0), 0xFF);
+        memory.cpu_write(memory_map::OAM0, 0x00);
+
+        memory.set_lcd_status(video_mode::SCANLINE, false);
+        assert_eq!(memory.cpu_fetch(0x8000), 0xFF);
+        assert_eq!(memory.cpu_fetch(memory_map::OAM0), 0xFF);
+        memory.cpu_write(0x8000, 0x00);
+        // The PPU itself still reads them
+        assert_eq!(memory.fetch(0x8000), 0x12);
+        assert_eq!(memory.fetch(memory_map::OAM0), 0x34);
+
+        // With the restrictions lifted, or the LCD off, the CPU always has access
+        memory.set_access_restrictions(false);
+        assert_eq!(memory.cpu_fetch(0x8000), 0x12);
+        memory.set_access_restrictions(true);
+        memory.write(memory_map::LCDC, 0x11);
+        assert_eq!(memory.cpu_fetch(memory_map::OAM0), 0x34);
+
+        memory.write(memory_map::LCDC, 0x91);
+        memory.set_lcd_status(video_mode::HBLANK, false);
+        memory.cpu_write(0x8000, 0x56);
+        memory.cpu_write(memory_map::OAM0, 0x78);
+        assert_eq!(memory.cpu_fetch(0x8000), 0x56);
+        assert_eq!(memory.cpu_fetch(memory_map::OAM0), 0x78);
+    }
+
+    #[test]
+    fn test_lcd_off_and_on() {
+        let mut memory = memory(0x00, 0x00);
+        let mut ppu = PPU::new();
+        next_frame(&mut ppu, &mut memory);
+        while memory.fetch(memory_map::LY) != 50 {
+            ppu.cycle(&mut memory, 1);
+        }
+        ppu.video.frame_buffer[0] = 0x03;
+        ppu.update_buffer = false;
+
+        // Turning the LCD off resets LY and the mode, and blanks the screen
+        memory.write(memory_map::LCDC, 0x11);
+        ppu.cycle(&mut memory, 1);
+        assert_eq!(memory.fetch(memory_map::LY), 0x00);
+        assert_eq!(mode(&mut memory), video_mode::HBLANK);
+        assert!(ppu.video.frame_buffer.iter().all(|&pixel| pixel == 0x00));
+        assert!(ppu.update_buffer);
+        ppu.update_buffer = false;
+        for _ in 0..20_000 {
+            ppu.cycle(&mut memory, 1);
+        }
+        assert_eq!(memory.fetch(memory_map::LY), 0x00);
+        assert!(!ppu.update_buffer);
+
+        // The first frame after turning it on is not displayed
+        memory.write(memory_map::LCDC, 0x91);
+        next_frame(&mut ppu, &mut memory);
+        assert!(!ppu.update_buffer);
+        next_frame(&mut ppu, &mut memory);
+        assert!(ppu.update_buffer);
+    }
+
+    /// Machine cycles spent in each mode, in order, until the next frame starts
+    fn mode_lengths(ppu: &mut PPU, memory: &mut Memory, renderer: Renderer) -> Vec<(u8, usize)> {
+        ppu.set_renderer(renderer);
+        next_frame(ppu, memory);
+        let mut lengths: Vec<(u8, usize)> = vec![(mode(memory), 0)];
+        loop {
+            let previous_mode: u8 = mode(memory);
+            ppu.cycle(memory, 1);
+            lengths.last_mut().unwrap().1 += 1;
+            if previous_mode == video_mode::VBLANK && mode(memory) == video_mode::SEARCH_OAM {
+                return lengths;
+            }
+            if mode(memory) != previous_mode {
+                lengths.push((mode(memory), 0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_and_frame_length() {
+        let mut memory = memory(0x00, 0x00);
+        let mut ppu = PPU::new();
+        let lengths = mode_lengths(&mut ppu, &mut memory, Renderer::Scanline);
+        assert_eq!(lengths.len(), 144 * 3 + 1);
+        assert_eq!(&lengths[0..3], &[(video_mode::SEARCH_OAM, 20), (video_mode::SCANLINE, 43), (video_mode::HBLANK, 51)]);
+        assert_eq!(lengths[144 * 3], (video_mode::VBLANK, 1140));
+        assert_eq!(lengths.iter().map(|&(_, cycles)| cycles).sum::<usize>(), 17_556);
+
+        // Mode 3 varies with the pixel FIFO, but lines keep their length
+        let lengths = mode_lengths(&mut ppu, &mut memory, Renderer::PixelFifo);
+        for line in lengths[0..(144 * 3)].chunks(3) {
+            assert_eq!(line.iter().map(|&(_, cycles)| cycles).sum::<usize>(), 114);
+        }
+        assert_eq!(lengths.iter().map(|&(_, cycles)| cycles).sum::<usize>(), 17_556);
+    }
+}
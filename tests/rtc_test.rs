@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::rtc::*;
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct FakeClock {
+        seconds: Rc<Cell<u64>>,
+    }
+
+    impl ClockSource for FakeClock {
+        fn now(&self) -> u64 {
+            self.seconds.get()
+        }
+    }
+
+    fn setup() -> (RealTimeClock, Rc<Cell<u64>>) {
+        let seconds = Rc::new(Cell::new(1_000));
+        let rtc = RealTimeClock::new(Box::new(FakeClock { seconds: seconds.clone() }));
+        (rtc, seconds)
+    }
+
+    #[test]
+    fn test_latch_sequence() {
+        let (mut rtc, seconds) = setup();
+        seconds.set(seconds.get() + 3_661); // 1 hour, 1 minute and 1 second later
+
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read(SECONDS), 0x00);
+
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read(SECONDS), 1);
+        assert_eq!(rtc.read(MINUTES), 1);
+        assert_eq!(rtc.read(HOURS), 1);
+        assert_eq!(rtc.read(DAYS_LOW), 0);
+        assert_eq!(rtc.read(DAYS_HIGH), 0);
+
+        // Latched values don't change until the next latch
+        seconds.set(seconds.get() + 10);
+        assert_eq!(rtc.read(SECONDS), 1);
+    }
+
+    #[test]
+    fn test_halt() {
+        let (mut rtc, seconds) = setup();
+        rtc.write(DAYS_HIGH, 0x40);
+        seconds.set(seconds.get() + 120);
+        rtc.latch();
+        assert_eq!(rtc.read(MINUTES), 0);
+        assert_eq!(rtc.read(DAYS_HIGH), 0x40);
+
+        rtc.write(DAYS_HIGH, 0x00);
+        seconds.set(seconds.get() + 59);
+        rtc.latch();
+        assert_eq!(rtc.read(SECONDS), 59);
+        assert_eq!(rtc.read(MINUTES), 0);
+    }
+
+    #[test]
+    fn test_day_counter_carry() {
+        let (mut rtc, seconds) = setup();
+        rtc.write(DAYS_LOW, 0xFF);
+        rtc.write(DAYS_HIGH, 0x01);
+        rtc.write(HOURS, 23);
+        rtc.write(MINUTES, 59);
+        rtc.write(SECONDS, 59);
+        seconds.set(seconds.get() + 1);
+        rtc.latch();
+        assert_eq!(rtc.registers(), [0, 0, 0, 0, 0x80]);
+    }
+
+    #[test]
+    fn test_save_state() {
+        let (mut rtc, seconds) = setup();
+        rtc.write(HOURS, 5);
+        rtc.latch();
+        let state = rtc.save_state();
+        assert_eq!(state.len(), SAVE_STATE_SIZE);
+
+        // Restored one minute after being saved
+        let (mut restored, restored_seconds) = setup();
+        restored_seconds.set(seconds.get() + 60);
+        restored.load_state(&state);
+        assert_eq!(restored.read(HOURS), 5);
+        restored.latch();
+        assert_eq!(restored.read(MINUTES), 1);
+
+        // The legacy layout has a 32-bit timestamp
+        let (mut legacy, _) = setup();
+        legacy.load_state(&state[..SAVE_STATE_SIZE_LEGACY]);
+        assert_eq!(legacy.registers()[2], 5);
+    }
+}
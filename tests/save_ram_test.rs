@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tests::oh_boy::hardware::system::System;
+    use tests::oh_boy::hardware::memory::Memory;
+    use tests::oh_boy::hardware::cartridge_types;
+    use tests::oh_boy::hardware::memory_map;
+    use tests::oh_boy::hardware::rtc;
+
+    const RAM_SIZE: usize = 0x2000;
+
+    /// Writes a 64kB ROM with 8kB of RAM to the temporary directory
+    fn rom_file(name: &str, cartridge_type: u8) -> PathBuf {
+        let path: PathBuf = env::temp_dir().join(format!("oh-boy-{}-{}.gb", name, cartridge_type));
+        let mut rom: Vec<u8> = vec![0; 0x10000];
+        rom[memory_map::RTC as usize] = cartridge_type;
+        rom[memory_map::ASIZ as usize] = 0x02;
+        File::create(&path).unwrap().write_all(&rom).unwrap();
+        path
+    }
+
+    fn remove(path: PathBuf) {
+        let _ = fs::remove_file(path.with_extension("sav"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_dirty_on_changes_only() {
+        let path = rom_file("dirty", cartridge_types::ROM_MBC1_RAM1);
+        let mut memory = Memory::new(path.to_string_lossy().into_owned(), None);
+
+        // The RAM is disabled
+        memory.write(0xA000, 0x42);
+        assert!(!memory.save_ram_dirty());
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0x42);
+        assert!(memory.save_ram_dirty());
+        memory.flush_save_ram().unwrap();
+        assert!(!memory.save_ram_dirty());
+        assert!(path.with_extension("sav").exists());
+        // The same value again
+        memory.write(0xA000, 0x42);
+        assert!(!memory.save_ram_dirty());
+        remove(path);
+    }
+
+    #[test]
+    fn test_not_dirty_without_battery() {
+        let path = rom_file("no-battery", cartridge_types::ROM_MBC1_RAM0);
+        let mut memory = Memory::new(path.to_string_lossy().into_owned(), None);
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0x42);
+        assert_eq!(memory.fetch(0xA000), 0x42);
+        assert!(!memory.save_ram_dirty());
+        remove(path);
+    }
+
+    #[test]
+    fn test_dirty_on_rtc_changes() {
+        let path = rom_file("rtc", cartridge_types::ROM_MBC3_TIMER_RAM_BATTERY);
+        let mut memory = Memory::new(path.to_string_lossy().into_owned(), None);
+        // The RAM (and the clock) is disabled
+        memory.write(0x4000, rtc::DAYS_HIGH);
+        memory.write(0xA000, 0x40);
+        assert!(!memory.save_ram_dirty());
+
+        // Halting the clock
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0x40);
+        assert!(memory.save_ram_dirty());
+        memory.flush_save_ram().unwrap();
+        assert!(!memory.save_ram_dirty());
+        // The same value again; the latch doesn't change the clock either
+        memory.write(0xA000, 0x40);
+        memory.write(0x6000, 0x00);
+        memory.write(0x6000, 0x01);
+        assert!(!memory.save_ram_dirty());
+
+        // Setting the clock (halted, so it holds still)
+        memory.write(0x4000, rtc::SECONDS);
+        memory.write(0xA000, 0x15);
+        assert!(memory.save_ram_dirty());
+        memory.flush_save_ram().unwrap();
+        memory.write(0x4000, rtc::DAYS_LOW);
+        memory.write(0xA000, 0x01);
+        assert!(memory.save_ram_dirty());
+        remove(path);
+    }
+
+    #[test]
+    fn test_save_ram_round_trip() {
+        let path = rom_file("round-trip", cartridge_types::ROM_MBC3_TIMER_RAM_BATTERY);
+        let mut system = System::new(path.to_string_lossy().into_owned(), None);
+        assert_eq!(system.export_save_ram().len(), RAM_SIZE + rtc::SAVE_STATE_SIZE);
+
+        let mut data: Vec<u8> = (0..RAM_SIZE).map(|i| (i * 7) as u8).collect();
+        // A halted clock at 1d 02:03:04, latched at 0d 00:00:05
+        let registers: [u8; 10] = [0x04, 0x03, 0x02, 0x01, 0x40, 0x05, 0x00, 0x00, 0x00, 0x40];
+        for register in registers.iter() {
+            data.extend_from_slice(&[*register, 0x00, 0x00, 0x00]);
+        }
+        let timestamp: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        for i in 0..8 {
+            data.push((timestamp >> (i * 8)) as u8);
+        }
+        system.import_save_ram(&data);
+        assert!(system.save_ram_dirty());
+
+        let exported: Vec<u8> = system.export_save_ram();
+        assert_eq!(exported.len(), data.len());
+        assert_eq!(&exported[..(RAM_SIZE + 40)], &data[..(RAM_SIZE + 40)]);
+        let mut exported_timestamp: u64 = 0;
+        for i in 0..8 {
+            exported_timestamp |= (exported[RAM_SIZE + 40 + i] as u64) << (i * 8);
+        }
+        assert!(exported_timestamp >= timestamp);
+
+        // The .sav file holds the same data, and is loaded by the next run
+        system.flush_save_ram().unwrap();
+        assert!(!system.save_ram_dirty());
+        let system = System::new(path.to_string_lossy().into_owned(), None);
+        assert_eq!(&system.export_save_ram()[..(RAM_SIZE + 40)], &data[..(RAM_SIZE + 40)]);
+        remove(path);
+    }
+}
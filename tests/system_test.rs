@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use tests::oh_boy::hardware::system::{System, CYCLES_PER_FRAME};
+    use tests::oh_boy::hardware::memory_map;
+
+    /// Writes a ROM running the program at 0x0100, and boots it without a BOOT ROM (the LCD
+    /// is on)
+    fn boot(name: &str, program: &[u8]) -> System {
+        let path: PathBuf = env::temp_dir().join(format!("oh-boy-system-{}.gb", name));
+        let mut rom: Vec<u8> = vec![0; 0x8000];
+        rom[0x0100..(0x0100 + program.len())].copy_from_slice(program);
+        File::create(&path).unwrap().write_all(&rom).unwrap();
+        let mut system = System::new(path.to_string_lossy().into_owned(), None);
+        let _ = fs::remove_file(path);
+        system.boot();
+        system
+    }
+
+    #[test]
+    fn test_frame_length() {
+        // JR -2
+        let mut system = boot("frame", &[0x18, 0xFE]);
+        // CYCLES_PER_FRAME counts machine cycles: a frame is 70224 clock cycles
+        assert_eq!(CYCLES_PER_FRAME * 4, 70_224);
+
+        let mut pictures: usize = 0;
+        for _ in 0..10 {
+            let cycles = system.run_frame();
+            assert!(cycles >= CYCLES_PER_FRAME && cycles < CYCLES_PER_FRAME + 3);
+            if system.frame_updated() {
+                pictures += 1;
+            }
+        }
+        assert_eq!(pictures, 10);
+
+        // LY wraps back to 0 every frame (17556 is a multiple of the 3 cycles of JR)
+        let mut frame_starts: Vec<usize> = Vec::new();
+        let mut emulated_cycles: usize = 0;
+        let mut ly: u8 = system.fetch(memory_map::LY);
+        while frame_starts.len() < 4 {
+            emulated_cycles += system.cycle();
+            let previous_ly: u8 = std::mem::replace(&mut ly, system.fetch(memory_map::LY));
+            if ly == 0 && previous_ly != 0 {
+                frame_starts.push(emulated_cycles);
+            }
+        }
+        let frame_lengths: Vec<usize> = frame_starts.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        assert_eq!(frame_lengths, vec![CYCLES_PER_FRAME; 3]);
+    }
+
+    /// Machine cycles between the changes of a register, over the given machine cycles
+    fn periods(system: &mut System, address: u16, cycles: usize) -> Vec<usize> {
+        let mut changes: Vec<usize> = Vec::new();
+        let mut emulated_cycles: usize = 0;
+        let mut word: u8 = system.fetch(address);
+        while emulated_cycles < cycles {
+            emulated_cycles += system.cycle();
+            if system.fetch(address) != word {
+                word = system.fetch(address);
+                changes.push(emulated_cycles);
+            }
+        }
+        changes.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+
+    #[test]
+    fn test_timer_rates() {
+        // The TAC clocks as machine cycles per TIMA increment
+        for &(tac, period) in [(0x04, 256), (0x05, 4), (0x06, 16), (0x07, 64)].iter() {
+            // LD A,tac; LDH (TAC),A; then NOPs
+            let mut system = boot(&format!("timer-{}", tac), &[0x3E, tac, 0xE0, 0x07]);
+            system.cycle();
+            system.cycle();
+            assert_eq!(periods(&mut system, memory_map::TIMA, 5 * period), vec![period; 4]);
+        }
+
+        // DIV counts every 64 machine cycles (16384 Hz), whatever the TAC clock
+        let mut system = boot("div", &[]);
+        assert_eq!(periods(&mut system, memory_map::DIV, 5 * 64), vec![64; 4]);
+    }
+}
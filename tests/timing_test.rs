@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::cpu::CPU;
+    use tests::oh_boy::hardware::timer::Timer;
+    use tests::oh_boy::hardware::memory::Memory;
+    use tests::oh_boy::hardware::bus::SystemBus;
+    use tests::oh_boy::hardware::memory_map;
+    use tests::oh_boy::hardware::cartridge::RomOnly;
+
+    /// Runs the program at 0x0100 with the timer at its fastest rate (TIMA counts every
+    /// 4 machine cycles)
+    fn run(program: &[u8], instructions: usize) -> (Memory, Vec<usize>) {
+        let mut rom: Vec<u8> = vec![0; 0x8000];
+        rom[0x0100..(0x0100 + program.len())].copy_from_slice(program);
+        let mut memory = Memory::from_cartridge(Box::new(RomOnly::new(rom, 0)));
+        let mut timer = Timer::new();
+        memory.write(memory_map::TAC, 0x05);
+        let mut cpu = CPU::new();
+        cpu.boot_expected();
+
+        let cycles: Vec<usize> = (0..instructions).map(|_| {
+            let mut bus = SystemBus::new(&mut memory, &mut timer, None);
+            let cycles = cpu.cycle(&mut bus);
+            bus.complete_instruction(cycles)
+        }).collect();
+        (memory, cycles)
+    }
+
+    #[test]
+    fn test_reads_on_their_machine_cycle() {
+        // LDH A,(TIMA); LDH (80h),A; LDH A,(TIMA); LDH (81h),A
+        let (mut memory, cycles) = run(&[0xF0, 0x05, 0xE0, 0x80, 0xF0, 0x05, 0xE0, 0x81], 4);
+        assert_eq!(cycles, vec![3, 3, 3, 3]);
+        // Read on the 3rd cycle, and on the 9th (after 2 increments)
+        assert_eq!(memory.fetch(0xFF80), 0x00);
+        assert_eq!(memory.fetch(0xFF81), 0x02);
+        assert_eq!(memory.fetch(memory_map::TIMA), 0x03);
+    }
+
+    #[test]
+    fn test_internal_cycles() {
+        // INC BC takes a cycle after its opcode fetch; the timer runs through it
+        let (mut memory, cycles) = run(&[0x03, 0x03, 0xF0, 0x05], 3);
+        assert_eq!(cycles, vec![2, 2, 3]);
+        assert_eq!(memory.fetch(memory_map::TIMA), 0x01);
+        let (mut memory, _) = run(&[0x03, 0x03, 0x03, 0x03], 4);
+        assert_eq!(memory.fetch(memory_map::TIMA), 0x02);
+    }
+}
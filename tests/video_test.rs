@@ -0,0 +1,245 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::video::*;
+    use tests::oh_boy::hardware::memory::Memory;
+    use tests::oh_boy::hardware::memory_map;
+    use tests::oh_boy::hardware::cartridge::RomOnly;
+
+    fn memory(lcdc: u8) -> Memory {
+        let mut memory = Memory::from_cartridge(Box::new(RomOnly::new(vec![0; 0x8000], 0)));
+        memory.write(memory_map::LCDC, lcdc);
+        memory.write(memory_map::LY, 0x00);
+        memory.write(memory_map::BGP, 0xE4);
+        memory.write(memory_map::OBP0, 0xE4);
+        memory.write(memory_map::OBP1, 0x1B);
+        memory
+    }
+
+    fn tile_row(memory: &mut Memory, character_code: u16, row: u16, lsb: u8, msb: u8) {
+        memory.write(0x8000 + character_code * 16 + row * 2, lsb);
+        memory.write(0x8000 + character_code * 16 + row * 2 + 1, msb);
+    }
+
+    fn object(memory: &mut Memory, index: u16, y: u8, x: u8, character_code: u8, attributes: u8) {
+        let address = memory_map::OAM0 + index * 4;
+        memory.write(address, y);
+        memory.write(address + 1, x);
+        memory.write(address + 2, character_code);
+        memory.write(address + 3, attributes);
+    }
+
+    fn render_line(memory: &mut Memory) -> Vec<u8> {
+        let mut video = Video::new();
+        video.search_oam(memory);
+        video.update_scanline(memory);
+        video.frame_buffer[0..FRAME_WIDTH].to_vec()
+    }
+
+    #[test]
+    fn test_x_priority_and_palettes() {
+        let mut memory = memory(0x82);
+        tile_row(&mut memory, 1, 0, 0xFF, 0x00);
+        tile_row(&mut memory, 2, 0, 0x00, 0xFF);
+        // The object with the smallest X wins, even if it comes later in OAM
+        object(&mut memory, 0, 16, 12, 2, 0x10);
+        object(&mut memory, 1, 16, 8, 1, 0x00);
+        // Not on the line
+        object(&mut memory, 2, 40, 8, 2, 0x00);
+
+        let line = render_line(&mut memory);
+        assert_eq!(&line[0..8], &[1; 8]);
+        // Color 2 through OBP1 (reversed)
+        assert_eq!(&line[8..12], &[1; 4]);
+        assert_eq!(&line[12..16], &[0; 4]);
+    }
+
+    #[test]
+    fn test_objects_per_line_limit() {
+        let mut memory = memory(0x82);
+        tile_row(&mut memory, 1, 0, 0x80, 0x00);
+        for index in 0..12 {
+            object(&mut memory, index, 16, 8 + (index as u8) * 8, 1, 0x00);
+        }
+
+        let line = render_line(&mut memory);
+        for index in 0..12 {
+            let expected: u8 = if index < 10 { 1 } else { 0 };
+            assert_eq!(line[index * 8], expected);
+        }
+    }
+
+    #[test]
+    fn test_flips_and_tall_objects() {
+        // 8x16 objects
+        let mut memory = memory(0x86);
+        tile_row(&mut memory, 4, 0, 0xF0, 0x00);
+        // Row 15 of the object (row 7 of the second tile), drawn on the first line by the Y flip
+        tile_row(&mut memory, 5, 7, 0x0F, 0xFF);
+        object(&mut memory, 0, 16, 8, 5, 0x40);
+        // Horizontal flip
+        object(&mut memory, 1, 16, 16, 4, 0x20);
+
+        let line = render_line(&mut memory);
+        assert_eq!(&line[0..8], &[2, 2, 2, 2, 3, 3, 3, 3]);
+        assert_eq!(&line[8..16], &[0, 0, 0, 0, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_background_priority() {
+        // BG on, with the Character Data at 8000
+        let mut memory = memory(0x93);
+        tile_row(&mut memory, 0, 0, 0x0F, 0x00);
+        tile_row(&mut memory, 1, 0, 0x00, 0xFF);
+        object(&mut memory, 0, 16, 8, 1, 0x80);
+        object(&mut memory, 1, 16, 16, 1, 0x00);
+
+        let line = render_line(&mut memory);
+        // The BG hides the object behind it, but for its color 0
+        assert_eq!(&line[0..8], &[2, 2, 2, 2, 1, 1, 1, 1]);
+        assert_eq!(&line[8..16], &[2; 8]);
+    }
+
+    /// BG tiles use color 1; window tiles (code 1, map at 9C00) use color 3
+    fn window_memory(wx: u8, wy: u8) -> Memory {
+        let mut memory = memory(0xF1);
+        for row in 0..8 {
+            tile_row(&mut memory, 0, row, 0xFF, 0x00);
+            tile_row(&mut memory, 1, row, 0xFF, 0xFF);
+        }
+        for block in 0..0x400 {
+            memory.write(0x9C00 + block, 0x01);
+        }
+        memory.write(memory_map::WX, wx);
+        memory.write(memory_map::WY, wy);
+        memory
+    }
+
+    fn render_frame(memory: &mut Memory, video: &mut Video, lines: usize) {
+        for ly in 0..lines {
+            memory.write(memory_map::LY, ly as u8);
+            video.search_oam(memory);
+            video.update_scanline(memory);
+        }
+    }
+
+    fn line(video: &Video, ly: usize) -> &[u8] {
+        &video.frame_buffer[(ly * FRAME_WIDTH)..((ly + 1) * FRAME_WIDTH)]
+    }
+
+    #[test]
+    fn test_window_position() {
+        let mut memory = window_memory(7 + 80, 2);
+        let mut video = Video::new();
+        render_frame(&mut memory, &mut video, 4);
+        assert!(line(&video, 1).iter().all(|&pixel| pixel == 1));
+        assert!(line(&video, 2)[0..80].iter().all(|&pixel| pixel == 1));
+        assert!(line(&video, 2)[80..].iter().all(|&pixel| pixel == 3));
+
+        // WX<7 starts at the left edge
+        let mut memory = window_memory(3, 0);
+        let mut video = Video::new();
+        render_frame(&mut memory, &mut video, 1);
+        assert!(line(&video, 0).iter().all(|&pixel| pixel == 3));
+    }
+
+    #[test]
+    fn test_window_line_counter() {
+        let mut memory = window_memory(7, 0);
+        // Window rows: 3 on row 1 of its first tile line only
+        for row in 0..8 {
+            tile_row(&mut memory, 1, row, 0x00, 0x00);
+        }
+        tile_row(&mut memory, 1, 1, 0xFF, 0xFF);
+        let mut video = Video::new();
+
+        memory.write(memory_map::LY, 0x00);
+        video.update_scanline(&mut memory);
+        // The window is disabled on line 1: its line counter doesn't advance
+        memory.write(memory_map::LCDC, 0xD1);
+        memory.write(memory_map::LY, 0x01);
+        video.update_scanline(&mut memory);
+        memory.write(memory_map::LCDC, 0xF1);
+        memory.write(memory_map::LY, 0x02);
+        video.update_scanline(&mut memory);
+
+        assert!(line(&video, 0).iter().all(|&pixel| pixel == 0));
+        assert!(line(&video, 1).iter().all(|&pixel| pixel == 1));
+        assert!(line(&video, 2).iter().all(|&pixel| pixel == 3));
+    }
+
+    #[test]
+    fn test_window_wx_166() {
+        let mut memory = window_memory(166, 0);
+        let mut video = Video::new();
+        render_frame(&mut memory, &mut video, 1);
+        assert!(line(&video, 0)[0..159].iter().all(|&pixel| pixel == 1));
+        assert_eq!(line(&video, 0)[159], 3);
+
+        // The next line is fully covered, even after WX moves the window off the screen
+        memory.write(memory_map::WX, 167);
+        memory.write(memory_map::LY, 0x01);
+        video.update_scanline(&mut memory);
+        assert!(line(&video, 1).iter().all(|&pixel| pixel == 3));
+        memory.write(memory_map::LY, 0x02);
+        video.update_scanline(&mut memory);
+        assert!(line(&video, 2).iter().all(|&pixel| pixel == 1));
+    }
+
+    #[test]
+    fn test_background_fine_scroll() {
+        // BG on, with the Character Data at 8000; tile 1 is color 1 and tile 2 is color 3
+        let mut memory = memory(0x91);
+        for row in 0..8 {
+            tile_row(&mut memory, 1, row, 0xFF, 0x00);
+            tile_row(&mut memory, 2, row, 0xFF, 0xFF);
+        }
+        memory.write(0x9800 + 1, 0x01);
+        memory.write(0x9800 + 20, 0x02);
+
+        // The first block is partially scrolled out, and a 21st block is partially drawn
+        memory.write(memory_map::SCX, 3);
+        let line = render_line(&mut memory);
+        assert_eq!(&line[0..5], &[0; 5]);
+        assert_eq!(&line[5..13], &[1; 8]);
+        assert!(line[13..157].iter().all(|&pixel| pixel == 0));
+        assert_eq!(&line[157..160], &[3; 3]);
+
+        // SCX and SCY wrap around the 32x32 blocks of the BG map
+        memory.write(0x9800 + 31, 0x01);
+        memory.write(memory_map::SCX, 253);
+        memory.write(memory_map::SCY, 250);
+        memory.write(memory_map::LY, 10);
+        let mut video = Video::new();
+        video.update_scanline(&mut memory);
+        let line = &video.frame_buffer[(10 * FRAME_WIDTH)..(11 * FRAME_WIDTH)];
+        assert_eq!(&line[0..3], &[1; 3]);
+        assert_eq!(&line[3..11], &[0; 8]);
+        assert_eq!(&line[11..19], &[1; 8]);
+    }
+
+    #[test]
+    fn test_background_8800_addressing() {
+        // BG and window on, both with the map at 9800 and the Character Data at 8800
+        let mut memory = memory(0xA1);
+        // Signed codes: 00h is the tile at 9000, FFh at 8FF0 and 80h at 8800
+        tile_row(&mut memory, 0x100, 0, 0xFF, 0x00);
+        tile_row(&mut memory, 0xFF, 0, 0x00, 0xFF);
+        tile_row(&mut memory, 0x80, 0, 0xFF, 0xFF);
+        memory.write(0x9800, 0x00);
+        memory.write(0x9800 + 1, 0xFF);
+        memory.write(0x9800 + 2, 0x80);
+        memory.write(0x9800 + 3, 0x01);
+        memory.write(memory_map::WX, 7 + 80);
+        memory.write(memory_map::WY, 0);
+
+        // The BG and the window read the same tiles
+        let line = render_line(&mut memory);
+        for &start in [0, 80].iter() {
+            assert_eq!(&line[start..(start + 8)], &[1; 8]);
+            assert_eq!(&line[(start + 8)..(start + 16)], &[2; 8]);
+            assert_eq!(&line[(start + 16)..(start + 24)], &[3; 8]);
+            assert_eq!(&line[(start + 24)..(start + 32)], &[0; 8]);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    extern crate oh_boy;
+    use tests::oh_boy::hardware::wav::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_header_and_samples() {
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), 48_000).unwrap();
+        writer.write_samples(&[0.0, 1.0, -1.0, 2.0]).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        assert_eq!(data.len(), 44 + 8);
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(&data[4..8], &[44, 0, 0, 0]);
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        // Stereo, 48 kHz, 16 bits
+        assert_eq!(&data[22..24], &[2, 0]);
+        assert_eq!(&data[24..28], &[0x80, 0xBB, 0, 0]);
+        assert_eq!(&data[34..36], &[16, 0]);
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(&data[40..44], &[8, 0, 0, 0]);
+        // Samples are clamped to the 16-bit range
+        assert_eq!(&data[44..], &[0x00, 0x00, 0xFF, 0x7F, 0x01, 0x80, 0xFF, 0x7F]);
+    }
+}